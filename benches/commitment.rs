@@ -0,0 +1,59 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use criterion::{criterion_group, BatchSize, Criterion};
+use rand::thread_rng;
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::SecretKey,
+    ristretto::{pedersen::PedersenCommitmentFactory, RistrettoSecretKey},
+};
+
+fn gen_opening() -> (RistrettoSecretKey, RistrettoSecretKey) {
+    let mut rng = thread_rng();
+    (
+        RistrettoSecretKey::random(&mut rng),
+        RistrettoSecretKey::random(&mut rng),
+    )
+}
+
+fn commit(c: &mut Criterion) {
+    let factory = PedersenCommitmentFactory::default();
+    c.bench_function("Pedersen commitment", move |b| {
+        b.iter_batched(gen_opening, |(k, v)| factory.commit(&k, &v), BatchSize::SmallInput);
+    });
+}
+
+fn commit_precomputed(c: &mut Criterion) {
+    let factory = PedersenCommitmentFactory::default().with_precomputation();
+    c.bench_function("Pedersen commitment (precomputed)", move |b| {
+        b.iter_batched(gen_opening, |(k, v)| factory.commit(&k, &v), BatchSize::SmallInput);
+    });
+}
+
+criterion_group!(
+name = commitments;
+config = Criterion::default().warm_up_time(Duration::from_millis(500));
+targets = commit, commit_precomputed
+);
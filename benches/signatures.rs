@@ -24,6 +24,21 @@ fn native_keypair(c: &mut Criterion) {
     });
 }
 
+fn batch_invert(c: &mut Criterion) {
+    c.bench_function("Batch invert 1,000 secret keys", |b| {
+        let mut rng = thread_rng();
+        b.iter_batched(
+            || {
+                (0..1000)
+                    .map(|_| RistrettoSecretKey::random(&mut rng))
+                    .collect::<Vec<_>>()
+            },
+            |mut keys| RistrettoSecretKey::batch_invert(&mut keys).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+}
+
 struct SigningData {
     k: RistrettoSecretKey,
     p: RistrettoPublicKey,
@@ -69,8 +84,22 @@ fn verify_message(c: &mut Criterion) {
     });
 }
 
+fn verify_message_vartime(c: &mut Criterion) {
+    c.bench_function("Verify RistrettoSchnorr (vartime)", move |b| {
+        b.iter_batched(
+            || {
+                let d = gen_keypair();
+                let s = RistrettoSchnorr::sign(d.k.clone(), d.r.clone(), &d.m.to_vec()).unwrap();
+                (d, s)
+            },
+            |(d, s)| assert!(s.verify_vartime(&d.p, &d.m)),
+            BatchSize::SmallInput,
+        );
+    });
+}
+
 criterion_group!(
 name = signatures;
 config = Criterion::default().warm_up_time(Duration::from_millis(500));
-targets = generate_secret_key, native_keypair, sign_message, verify_message
+targets = generate_secret_key, native_keypair, batch_invert, sign_message, verify_message, verify_message_vartime
 );
@@ -0,0 +1,171 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A uniform byte encoding for the crypto objects in this crate.
+//!
+//! Keys, commitments and signatures each have their own byte APIs, which differ slightly in how strict they are and
+//! in whether they borrow their bytes. [CanonicalBytes] gives generic code a single way to serialize and
+//! deserialize any of them. Decoding is strict: only the encoding that `to_canonical_bytes` would produce is
+//! accepted, so every value has exactly one byte representation.
+
+use std::convert::TryFrom;
+
+use tari_utilities::{ByteArray, ByteArrayError};
+
+use crate::{
+    commitment::HomomorphicCommitment,
+    keys::{PublicKey, SecretKey},
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    signatures::SchnorrSignature,
+};
+
+/// Convert a crypto object to and from its canonical byte encoding.
+pub trait CanonicalBytes: Sized {
+    /// The canonical byte encoding of `self`
+    fn to_canonical_bytes(&self) -> Vec<u8>;
+
+    /// Decode a value from its canonical encoding. Returns an error if `bytes` has the wrong length, or is not the
+    /// canonical encoding of any value.
+    fn from_canonical_slice(bytes: &[u8]) -> Result<Self, ByteArrayError>;
+}
+
+/// A secret key is encoded as its 32-byte little-endian scalar. Unlike [ByteArray::from_bytes], decoding rejects
+/// scalars that are not reduced mod _l_.
+impl CanonicalBytes for RistrettoSecretKey {
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_canonical_slice(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        RistrettoSecretKey::try_from(bytes)
+    }
+}
+
+/// A public key is encoded as its 32-byte compressed Ristretto point, and decoded with
+/// [RistrettoPublicKey::from_canonical_bytes].
+impl CanonicalBytes for RistrettoPublicKey {
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_canonical_slice(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        let bytes = <[u8; 32]>::try_from(bytes).map_err(|_| ByteArrayError::IncorrectLength)?;
+        RistrettoPublicKey::from_canonical_bytes(&bytes)
+    }
+}
+
+/// A commitment has the same encoding as the public key it wraps.
+impl<P> CanonicalBytes for HomomorphicCommitment<P>
+where P: PublicKey + CanonicalBytes
+{
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.as_public_key().to_canonical_bytes()
+    }
+
+    fn from_canonical_slice(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        let p = P::from_canonical_slice(bytes)?;
+        Ok(HomomorphicCommitment::from_public_key(&p))
+    }
+}
+
+/// A Schnorr signature is encoded as the public nonce followed by the signature scalar.
+impl<P, K> CanonicalBytes for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K> + CanonicalBytes,
+    K: SecretKey + CanonicalBytes,
+{
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.get_public_nonce().to_canonical_bytes();
+        bytes.extend_from_slice(&self.get_signature().to_canonical_bytes());
+        bytes
+    }
+
+    fn from_canonical_slice(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        if bytes.len() != P::key_length() + K::key_length() {
+            return Err(ByteArrayError::IncorrectLength);
+        }
+        let (nonce, signature) = bytes.split_at(P::key_length());
+        Ok(SchnorrSignature::new(
+            P::from_canonical_slice(nonce)?,
+            K::from_canonical_slice(signature)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Debug;
+
+    use rand::thread_rng;
+    use tari_utilities::ByteArrayError;
+
+    use super::CanonicalBytes;
+    use crate::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{PublicKey, SecretKey},
+        ristretto::{
+            pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+            test_common::add_group_order,
+            RistrettoPublicKey,
+            RistrettoSchnorr,
+            RistrettoSecretKey,
+        },
+    };
+
+    fn round_trip<T: CanonicalBytes + PartialEq + Debug>(value: &T, len: usize) {
+        let bytes = value.to_canonical_bytes();
+        assert_eq!(bytes.len(), len);
+        assert_eq!(&T::from_canonical_slice(&bytes).unwrap(), value);
+        assert!(matches!(
+            T::from_canonical_slice(&bytes[1..]),
+            Err(ByteArrayError::IncorrectLength)
+        ));
+    }
+
+    #[test]
+    fn canonical_round_trips() {
+        let mut rng = thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        round_trip(&k, 32);
+        round_trip(&p, 32);
+        let c = PedersenCommitmentFactory::default().commit_value(&k, 1234);
+        round_trip(&c, 32);
+        let r = RistrettoSecretKey::random(&mut rng);
+        let sig = RistrettoSchnorr::sign(k, r, b"canonical bytes").unwrap();
+        round_trip(&sig, 64);
+    }
+
+    #[test]
+    fn non_canonical_encodings_are_rejected() {
+        // l + 1 is not a reduced scalar
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        let l_plus_one = add_group_order(&one);
+        assert!(RistrettoSecretKey::from_canonical_slice(&l_plus_one).is_err());
+        // 0xff.. is not a valid point encoding
+        assert!(RistrettoPublicKey::from_canonical_slice(&[0xff; 32]).is_err());
+        assert!(PedersenCommitment::from_canonical_slice(&[0xff; 32]).is_err());
+        let mut sig = [0u8; 64];
+        sig[32..].copy_from_slice(&l_plus_one);
+        assert!(RistrettoSchnorr::from_canonical_slice(&sig).is_err());
+    }
+}
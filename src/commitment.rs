@@ -22,12 +22,14 @@
 
 use std::{
     cmp::Ordering,
+    convert::TryFrom,
+    fmt,
     hash::{Hash, Hasher},
-    ops::{Add, Mul, Sub},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
 };
 
 use serde::{Deserialize, Serialize};
-use tari_utilities::{ByteArray, ByteArrayError};
+use tari_utilities::{hex::Hex, ByteArray, ByteArrayError};
 
 use crate::keys::{PublicKey, SecretKey};
 
@@ -47,6 +49,14 @@ use crate::keys::{PublicKey, SecretKey};
 ///   C_2 &= v_2.G + k_2.H \\\\
 ///   \therefore C_1 + C_2 &= (v_1 + v_2)G + (k_1 + k_2)H
 /// \end{aligned} $$
+///
+/// The default commitment wraps the default public key. For Ristretto this is the identity point, i.e. a commitment
+/// to zero with a zero blinding factor, equal to [zero](HomomorphicCommitmentFactory::zero), so it is the neutral
+/// starting element when accumulating commitments.
+///
+/// A commitment can only be decoded from its unique canonical encoding. [from_bytes](ByteArray::from_bytes) rejects
+/// any input that does not re-encode to the same bytes, and deserialization goes through the public key's decoder,
+/// which for Ristretto already rejects every non-canonical point encoding.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HomomorphicCommitment<P>(pub(crate) P);
 
@@ -60,13 +70,28 @@ where P: PublicKey
     pub fn from_public_key(p: &P) -> HomomorphicCommitment<P> {
         HomomorphicCommitment(p.clone())
     }
+
+    /// Returns true if the commitment's encoding decodes to a valid point of the underlying group.
+    ///
+    /// Canonical form is a property of an encoding, and is enforced when a commitment is decoded. For Ristretto-based
+    /// commitments this therefore always holds: decompression rejects non-canonical encodings, and the encoding of a
+    /// commitment is always the canonical compression of its point.
+    pub fn is_canonical(&self) -> bool {
+        P::from_bytes(self.0.as_bytes()).is_ok()
+    }
 }
 
 impl<P> ByteArray for HomomorphicCommitment<P>
 where P: PublicKey
 {
+    /// Decode a commitment, rejecting any encoding that is not canonical
     fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
         let p = P::from_bytes(bytes)?;
+        if p.as_bytes() != bytes {
+            return Err(ByteArrayError::ConversionError(
+                "Non-canonical commitment encoding".to_string(),
+            ));
+        }
         Ok(Self(p))
     }
 
@@ -75,6 +100,26 @@ where P: PublicKey
     }
 }
 
+/// Convert a byte slice into a commitment. This has the same length and encoding requirements as the underlying
+/// public key, i.e. exactly 32 bytes representing a canonically encoded point for Ristretto-based commitments.
+impl<P> TryFrom<&[u8]> for HomomorphicCommitment<P>
+where P: PublicKey
+{
+    type Error = ByteArrayError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<P> AsRef<[u8]> for HomomorphicCommitment<P>
+where P: PublicKey
+{
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
 impl<P> PartialOrd for HomomorphicCommitment<P>
 where P: PublicKey
 {
@@ -104,6 +149,20 @@ where
     }
 }
 
+/// Add two owned commitments together, e.g. when folding over an iterator of commitments. Note! There is no check that
+/// the bases are equal.
+impl<P> Add for HomomorphicCommitment<P>
+where
+    P: PublicKey,
+    for<'a> &'a P: Add<&'a P, Output = P>,
+{
+    type Output = HomomorphicCommitment<P>;
+
+    fn add(self, rhs: HomomorphicCommitment<P>) -> Self::Output {
+        HomomorphicCommitment(&self.0 + &rhs.0)
+    }
+}
+
 /// Add a public key to a commitment. Note! There is no check that the bases are equal.
 impl<'a, 'b, P> Add<&'b P> for &'b HomomorphicCommitment<P>
 where
@@ -117,6 +176,24 @@ where
     }
 }
 
+/// Add a commitment in place, e.g. when accumulating a sum. Note! There is no check that the bases are equal.
+impl<'a, P> AddAssign<&'a HomomorphicCommitment<P>> for HomomorphicCommitment<P>
+where P: PublicKey + AddAssign<&'a P>
+{
+    fn add_assign(&mut self, rhs: &'a HomomorphicCommitment<P>) {
+        self.0 += &rhs.0;
+    }
+}
+
+/// Subtract a commitment in place. Note! There is no check that the bases are equal.
+impl<'a, P> SubAssign<&'a HomomorphicCommitment<P>> for HomomorphicCommitment<P>
+where P: PublicKey + SubAssign<&'a P>
+{
+    fn sub_assign(&mut self, rhs: &'a HomomorphicCommitment<P>) {
+        self.0 -= &rhs.0;
+    }
+}
+
 /// Subtracts the left commitment from the right commitment. Note! There is no check that the bases are equal.
 impl<'b, P> Sub for &'b HomomorphicCommitment<P>
 where
@@ -130,6 +207,33 @@ where
     }
 }
 
+/// Negate a commitment. Negating \\( C = k.G + v.H \\) gives \\( -C = (-k).G + (-v).H \\), a commitment to the
+/// negated value with the negated blinding factor, so that \\( C + (-C) \\) is the identity. This is how outputs
+/// are subtracted in balance equations.
+impl<'a, P> Neg for &'a HomomorphicCommitment<P>
+where
+    P: PublicKey,
+    &'a P: Neg<Output = P>,
+{
+    type Output = HomomorphicCommitment<P>;
+
+    fn neg(self) -> Self::Output {
+        HomomorphicCommitment(-&self.0)
+    }
+}
+
+impl<P> Neg for HomomorphicCommitment<P>
+where
+    P: PublicKey,
+    for<'a> &'a P: Neg<Output = P>,
+{
+    type Output = HomomorphicCommitment<P>;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
 /// Multiply the commitment with a private key
 impl<'a, 'b, P, K> Mul<&'b K> for &'a HomomorphicCommitment<P>
 where
@@ -145,6 +249,24 @@ where
     }
 }
 
+/// Formats the commitment as lowercase hex, e.g. 64 characters for a Ristretto commitment, with no prefix. The hex is
+/// the same as the one wrapped by the `Debug` output.
+impl<P: PublicKey> fmt::Display for HomomorphicCommitment<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Formats the commitment as lowercase hex, as for `Display`. The alternate flag (`{:#x}`) adds a `0x` prefix.
+impl<P: PublicKey> fmt::LowerHex for HomomorphicCommitment<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", self.to_hex())
+    }
+}
+
 impl<P: PublicKey> Hash for HomomorphicCommitment<P> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write(self.as_bytes())
@@ -159,6 +281,11 @@ impl<P: PublicKey> PartialEq for HomomorphicCommitment<P> {
 
 impl<P: PublicKey> Eq for HomomorphicCommitment<P> {}
 
+/// A factory for creating homomorphic commitments over a fixed set of generators.
+///
+/// This trait is object safe once the public key type is fixed, so factories with different generators can be chosen
+/// at runtime and stored behind a trait object, e.g.
+/// `Box<dyn HomomorphicCommitmentFactory<P = RistrettoPublicKey>>`.
 pub trait HomomorphicCommitmentFactory {
     type P: PublicKey;
 
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use serde::{Deserialize, Serialize};
+use tari_utilities::ByteArrayError;
 use thiserror::Error;
 
 #[derive(Debug, Clone, Error, PartialEq, Deserialize, Serialize)]
@@ -36,3 +37,44 @@ pub enum RangeProofError {
     #[error("Invalid range proof rewind, the rewind keys provided must be invalid")]
     InvalidRewind,
 }
+
+#[derive(Debug, Clone, Error, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CommitmentError {
+    #[error("A commitment generator may not be the identity point")]
+    IdentityGenerator,
+    #[error("The commitment generators must be distinct")]
+    DuplicateGenerators,
+    #[error("The number of values ({values}) does not match the number of value generators ({generators})")]
+    VectorLengthMismatch { values: usize, generators: usize },
+    #[error("The committed value is not in the set")]
+    ValueNotInSet,
+    #[error("The fixed point amount does not fit in a u64")]
+    FixedPointOverflow,
+    #[error("The fractional part of a fixed point amount must have at most the given number of decimal places")]
+    InvalidFractionalAmount,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq, Deserialize, Serialize)]
+pub enum KeyError {
+    #[error("The scalar is zero and cannot be inverted")]
+    ZeroScalar,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SerializationError {
+    #[error("Unsupported serialization version: {0}")]
+    VersionMismatch(u8),
+    #[error("The serialized data has an incorrect length")]
+    IncorrectLength,
+    #[error("The serialized data is not a valid encoding: {0}")]
+    InvalidEncoding(String),
+}
+
+impl From<ByteArrayError> for SerializationError {
+    fn from(e: ByteArrayError) -> Self {
+        match e {
+            ByteArrayError::IncorrectLength => SerializationError::IncorrectLength,
+            e => SerializationError::InvalidEncoding(e.to_string()),
+        }
+    }
+}
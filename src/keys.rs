@@ -48,6 +48,18 @@ use tari_utilities::ByteArray;
 pub trait SecretKey: ByteArray + Clone + PartialEq + Eq + Add<Output = Self> + Default {
     fn key_length() -> usize;
     fn random<R: Rng + CryptoRng>(rng: &mut R) -> Self;
+
+    /// Generate a random secret key that is guaranteed not to be zero. [random](SecretKey::random) draws uniformly from
+    /// the whole scalar field, so it can in principle return zero, which is not a valid key for signing. This draws
+    /// keys until a non-zero one turns up, which in practice is always the first.
+    fn random_non_zero<R: Rng + CryptoRng>(rng: &mut R) -> Self {
+        loop {
+            let k = Self::random(rng);
+            if k != Self::default() {
+                return k;
+            }
+        }
+    }
 }
 
 //----------------------------------------   Public Keys  ----------------------------------------//
@@ -68,8 +80,14 @@ pub trait PublicKey:
 
     fn batch_mul(scalars: &[Self::K], points: &[Self]) -> Self;
 
+    /// Returns true if this key is the identity element of the group. The default implementation relies on
+    /// `Default` returning the identity, which is the case for the implementations in this crate.
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
     fn random_keypair<R: Rng + CryptoRng>(rng: &mut R) -> (Self::K, Self) {
-        let k = Self::K::random(rng);
+        let k = Self::K::random_non_zero(rng);
         let pk = Self::from_secret_key(&k);
         (k, pk)
     }
@@ -3,6 +3,7 @@ extern crate lazy_static;
 
 #[macro_use]
 pub mod macros;
+pub mod canonical_bytes;
 pub mod commitment;
 pub mod hash;
 pub mod keys;
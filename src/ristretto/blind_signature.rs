@@ -0,0 +1,195 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Blind Schnorr signatures on Ristretto
+//!
+//! A blind signature lets a user obtain a valid [RistrettoSchnorr] signature on a message from a signer, without the
+//! signer learning the message, or being able to link the final signature to the signing session that produced it.
+//!
+//! The protocol runs as follows, where the signer has the key pair \\( (k, P) \\):
+//! 1. The signer chooses a random nonce, \\( r \\), and sends the user \\( R = r.G \\).
+//! 2. The user chooses two random blinding factors, \\( \alpha \\) and \\( \beta \\), and calculates
+//!   \\( R' = R + \alpha.G + \beta.P \\) and \\( e' = H(R' || P || m) \\). The user sends the blinded challenge,
+//!   \\( e = e' + \beta \\) to the signer.
+//! 3. The signer replies with \\( s = r + e.k \\).
+//! 4. The user unblinds the signature by calculating \\( s' = s + \alpha \\). The signature \\( (R', s') \\) is a
+//!  standard Schnorr signature on \\( m \\), and verifies with [verify_message](RistrettoSchnorr::verify_message).
+//!
+//! # Security
+//!
+//! Plain blind Schnorr signatures are NOT secure when the signer runs several sessions concurrently. A user who opens
+//! enough sessions at once can combine the signer's responses into one more valid signature than the number of
+//! sessions, using the ROS attack of Benhamouda, Lepoint, Loss, Orrù and Raykova (2021). The signer MUST therefore run
+//! sessions one at a time, and not hand out the public nonce of a new session until the previous session has either
+//! been signed or abandoned.
+
+use rand::{CryptoRng, Rng};
+
+use crate::{
+    hash::blake2::Blake256,
+    keys::{PublicKey, SecretKey},
+    ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+};
+
+/// The signer's half of the blind signature protocol. Each signer draws its own nonce, and consumes itself when
+/// signing, so that a nonce cannot be used to sign more than one blinded challenge. Only one session may be open at a
+/// time; see the [module documentation](self) for why.
+pub struct BlindSigner {
+    secret: RistrettoSecretKey,
+    nonce: RistrettoSecretKey,
+    public_key: RistrettoPublicKey,
+    public_nonce: RistrettoPublicKey,
+}
+
+impl BlindSigner {
+    /// Create a new signing session for the given secret key, drawing a fresh nonce from `rng`
+    pub fn new<R: Rng + CryptoRng>(secret: RistrettoSecretKey, rng: &mut R) -> Self {
+        let nonce = RistrettoSecretKey::random_non_zero(rng);
+        let public_key = RistrettoPublicKey::from_secret_key(&secret);
+        let public_nonce = RistrettoPublicKey::from_secret_key(&nonce);
+        BlindSigner {
+            secret,
+            nonce,
+            public_key,
+            public_nonce,
+        }
+    }
+
+    /// The signer's public key, _P_
+    pub fn public_key(&self) -> &RistrettoPublicKey {
+        &self.public_key
+    }
+
+    /// The public nonce, _R_, that must be sent to the user before they can blind their challenge
+    pub fn public_nonce(&self) -> &RistrettoPublicKey {
+        &self.public_nonce
+    }
+
+    /// Sign the blinded challenge received from the user, returning \\( s = r + e.k \\).
+    pub fn sign_blinded(self, blinded_challenge: &RistrettoSecretKey) -> RistrettoSecretKey {
+        &self.nonce + &(blinded_challenge * &self.secret)
+    }
+}
+
+/// The secret values the user needs to keep in order to unblind the signer's response.
+pub struct UnblindingFactors {
+    alpha: RistrettoSecretKey,
+    public_nonce: RistrettoPublicKey,
+    challenge: RistrettoSecretKey,
+}
+
+impl UnblindingFactors {
+    /// The blinded public nonce, _R'_, that will appear in the final signature
+    pub fn public_nonce(&self) -> &RistrettoPublicKey {
+        &self.public_nonce
+    }
+
+    /// The (unblinded) challenge, _e'_, that the final signature will verify against
+    pub fn challenge(&self) -> &RistrettoSecretKey {
+        &self.challenge
+    }
+}
+
+/// Blind the challenge for `message`, given the signer's public nonce and public key. Returns the blinded challenge
+/// that should be sent to the signer, and the factors that are needed to unblind the signer's response.
+pub fn blind<R: Rng + CryptoRng>(
+    message: &[u8],
+    public_nonce: &RistrettoPublicKey,
+    public_key: &RistrettoPublicKey,
+    rng: &mut R,
+) -> (RistrettoSecretKey, UnblindingFactors) {
+    let alpha = RistrettoSecretKey::random(rng);
+    let beta = RistrettoSecretKey::random(rng);
+    // R' = R + αG + βP
+    let blinded_nonce = public_nonce + &RistrettoPublicKey::from_secret_key(&alpha) + &beta * public_key;
+    let e = RistrettoSchnorr::message_challenge::<Blake256>(&blinded_nonce, public_key, message);
+    let blinded_challenge = &e + &beta;
+    let factors = UnblindingFactors {
+        alpha,
+        public_nonce: blinded_nonce,
+        challenge: e,
+    };
+    (blinded_challenge, factors)
+}
+
+/// Unblind the signer's response, producing a Schnorr signature \\( (R', s + \alpha) \\) on the message that verifies
+/// with [verify_message](RistrettoSchnorr::verify_message) against the signer's public key.
+pub fn unblind(blinded_signature: &RistrettoSecretKey, factors: &UnblindingFactors) -> RistrettoSchnorr {
+    let s = blinded_signature + &factors.alpha;
+    RistrettoSchnorr::new(factors.public_nonce.clone(), s)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hash::blake2::Blake256,
+        keys::PublicKey,
+        ristretto::{
+            blind_signature::{blind, unblind, BlindSigner},
+            RistrettoPublicKey,
+            RistrettoSchnorr,
+        },
+    };
+
+    #[test]
+    fn blind_sign_round_trip() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let signer = BlindSigner::new(k, &mut rng);
+        let signer_nonce = signer.public_nonce().clone();
+        let message = b"Thief of Time";
+        let (blinded_challenge, factors) = blind(message, &signer_nonce, &p, &mut rng);
+        let blinded_sig = signer.sign_blinded(&blinded_challenge);
+        let sig = unblind(&blinded_sig, &factors);
+        // The signer never sees R' or e'
+        assert_ne!(sig.get_public_nonce(), &signer_nonce);
+        assert_ne!(factors.challenge(), &blinded_challenge);
+        let e = RistrettoSchnorr::message_challenge::<Blake256>(sig.get_public_nonce(), &p, message);
+        assert_eq!(&e, factors.challenge());
+        assert!(sig.verify_message(&p, message));
+        // The signature is only good for the original message
+        assert!(!sig.verify_message(&p, b"Night Watch"));
+    }
+
+    #[test]
+    fn blinded_response_does_not_verify_directly() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let signer = BlindSigner::new(k, &mut rng);
+        let signer_nonce = signer.public_nonce().clone();
+        let (blinded_challenge, factors) = blind(b"Hogfather", &signer_nonce, &p, &mut rng);
+        let blinded_sig = signer.sign_blinded(&blinded_challenge);
+        let sig = RistrettoSchnorr::new(signer_nonce, blinded_sig);
+        assert!(!sig.verify(&p, factors.challenge()));
+        assert!(!sig.verify_message(&p, b"Hogfather"));
+    }
+
+    #[test]
+    fn each_session_has_a_fresh_nonce() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let s1 = BlindSigner::new(k.clone(), &mut rng);
+        let s2 = BlindSigner::new(k, &mut rng);
+        assert_eq!(s1.public_key(), s2.public_key());
+        assert_ne!(s1.public_nonce(), s2.public_nonce());
+    }
+}
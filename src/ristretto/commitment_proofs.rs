@@ -0,0 +1,244 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Zero-knowledge proofs about the values hidden in Pedersen commitments
+//!
+//! An `EqualityProof` convinces a verifier that two commitments, \\( C_1 = k_1.G + v.H \\) and
+//! \\( C_2 = k_2.G + v.H \\), hide the same value without revealing it. If the values are equal, the difference
+//! \\( C_1 - C_2 = (k_1 - k_2).G \\) is a commitment to zero, and the prover demonstrates knowledge of its discrete
+//! log with respect to _G_ using a Schnorr proof. If the values differ, the prover would have to know the discrete log
+//! of _H_ with respect to _G_ to produce a valid proof.
+//!
+//! The prover calculates \\( R = r.G \\), the Fiat-Shamir challenge \\( e = H(G || H || C_1 || C_2 || R) \\), and
+//! \\( s = r + e(k_1 - k_2) \\). A verifier accepts if \\( s.G = R + e(C_1 - C_2) \\).
+//!
+//! A commitment to zero, \\( C = k.G \\), such as the excess of a balanced transaction, is proven the same way with
+//! [prove_commitment_is_zero](PedersenCommitmentFactory::prove_commitment_is_zero).
+
+use blake2::Blake2b;
+use digest::Digest;
+use tari_utilities::ByteArray;
+
+use crate::{
+    hash::blake2::Blake256,
+    ristretto::{
+        pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+        RistrettoPublicKey,
+        RistrettoSchnorr,
+        RistrettoSecretKey,
+    },
+    signatures::SchnorrSignature,
+};
+
+const EQUALITY_CHALLENGE_PERSONA: &[u8] = b"tari.eq_proof";
+const EQUALITY_NONCE_LABEL: &[u8] = b"tari.eq_proof.nonce";
+const ZERO_CHALLENGE_PERSONA: &[u8] = b"tari.zero_proof";
+const ZERO_NONCE_LABEL: &[u8] = b"tari.zero_proof.nonce";
+
+/// A non-interactive proof that two Pedersen commitments hide the same value
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EqualityProof {
+    R: RistrettoPublicKey,
+    s: RistrettoSecretKey,
+}
+
+impl EqualityProof {
+    /// Prove that `c1`, with blinding factor `k1`, and `c2`, with blinding factor `k2`, commit to the same value using
+    /// the generators of `factory`.
+    ///
+    /// The nonce is derived deterministically from the blinding factors, the generators and the commitments, so a
+    /// nonce is only ever repeated for an identical proof.
+    #[allow(non_snake_case)]
+    pub fn prove_same_value(
+        factory: &PedersenCommitmentFactory,
+        c1: &PedersenCommitment,
+        c2: &PedersenCommitment,
+        k1: &RistrettoSecretKey,
+        k2: &RistrettoSecretKey,
+    ) -> Self {
+        let hash = Blake2b::new()
+            .chain(EQUALITY_NONCE_LABEL)
+            .chain(k1.as_bytes())
+            .chain(k2.as_bytes())
+            .chain(factory.G.compress().as_bytes())
+            .chain(factory.H.compress().as_bytes())
+            .chain(c1.as_bytes())
+            .chain(c2.as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash);
+        let r = RistrettoSecretKey::from_uniform_bytes(&bytes);
+        let R = RistrettoPublicKey::new_from_pk(r.0 * factory.G);
+        let e = EqualityProof::challenge(factory, c1, c2, &R);
+        let s = &r + &(&e * &(k1 - k2));
+        EqualityProof { R, s }
+    }
+
+    /// Returns true if this proof shows that `c1` and `c2` commit to the same value using the generators of
+    /// `factory`.
+    pub fn verify_same_value(
+        &self,
+        factory: &PedersenCommitmentFactory,
+        c1: &PedersenCommitment,
+        c2: &PedersenCommitment,
+    ) -> bool {
+        let e = EqualityProof::challenge(factory, c1, c2, &self.R);
+        let difference = c1 - c2;
+        let lhs = RistrettoPublicKey::new_from_pk(self.s.0 * factory.G);
+        let rhs = &self.R + &(&e * difference.as_public_key());
+        lhs == rhs
+    }
+
+    #[allow(non_snake_case)]
+    fn challenge(
+        factory: &PedersenCommitmentFactory,
+        c1: &PedersenCommitment,
+        c2: &PedersenCommitment,
+        R: &RistrettoPublicKey,
+    ) -> RistrettoSecretKey {
+        let hash = Blake256::with_params(&[], &[], EQUALITY_CHALLENGE_PERSONA)
+            .chain(factory.G.compress().as_bytes())
+            .chain(factory.H.compress().as_bytes())
+            .chain(c1.as_bytes())
+            .chain(c2.as_bytes())
+            .chain(R.as_bytes())
+            .finalize();
+        RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+    }
+}
+
+impl PedersenCommitmentFactory {
+    /// Prove that `commitment` is a commitment to a value of zero, \\( C = k.G \\), with the given `blinding` factor,
+    /// _k_. The proof is a Schnorr signature by _k_ against the factory's blinding generator, with the challenge
+    /// \\( e = H(G || H || C || R) \\) binding it to the commitment.
+    ///
+    /// The nonce is derived deterministically from the blinding factor and the commitment. If the commitment is not
+    /// to zero, the proof will not verify.
+    #[allow(non_snake_case)]
+    pub fn prove_commitment_is_zero(
+        &self,
+        commitment: &PedersenCommitment,
+        blinding: &RistrettoSecretKey,
+    ) -> RistrettoSchnorr {
+        let hash = Blake2b::new()
+            .chain(ZERO_NONCE_LABEL)
+            .chain(blinding.as_bytes())
+            .chain(commitment.as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash);
+        let r = RistrettoSecretKey::from_uniform_bytes(&bytes);
+        let R = RistrettoPublicKey::new_from_pk(r.0 * self.G);
+        let e = self.zero_challenge(commitment, &R);
+        let s = &r + &(&e * blinding);
+        SchnorrSignature::new(R, s)
+    }
+
+    /// Returns true if `proof`, made by
+    /// [prove_commitment_is_zero](PedersenCommitmentFactory::prove_commitment_is_zero), shows that `commitment` is
+    /// a commitment to zero, i.e. that \\( s.G = R + e.C \\).
+    pub fn verify_commitment_is_zero(&self, commitment: &PedersenCommitment, proof: &RistrettoSchnorr) -> bool {
+        let e = self.zero_challenge(commitment, proof.get_public_nonce());
+        let lhs = RistrettoPublicKey::new_from_pk(proof.get_signature().0 * self.G);
+        let rhs = proof.get_public_nonce() + &(&e * commitment.as_public_key());
+        lhs == rhs
+    }
+
+    #[allow(non_snake_case)]
+    fn zero_challenge(&self, commitment: &PedersenCommitment, R: &RistrettoPublicKey) -> RistrettoSecretKey {
+        let hash = Blake256::with_params(&[], &[], ZERO_CHALLENGE_PERSONA)
+            .chain(self.G.compress().as_bytes())
+            .chain(self.H.compress().as_bytes())
+            .chain(commitment.as_bytes())
+            .chain(R.as_bytes())
+            .finalize();
+        RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::SecretKey,
+        ristretto::{commitment_proofs::EqualityProof, pedersen::PedersenCommitmentFactory, RistrettoSecretKey},
+    };
+
+    #[test]
+    fn equal_values_verify() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let c1 = factory.commit_value(&k1, 1_000);
+        let c2 = factory.commit_value(&k2, 1_000);
+        let proof = EqualityProof::prove_same_value(&factory, &c1, &c2, &k1, &k2);
+        assert!(proof.verify_same_value(&factory, &c1, &c2));
+        // The proof is bound to the order of the commitments
+        assert!(!proof.verify_same_value(&factory, &c2, &c1));
+        // Proving the same statement again gives the same proof
+        assert_eq!(EqualityProof::prove_same_value(&factory, &c1, &c2, &k1, &k2), proof);
+    }
+
+    #[test]
+    fn different_values_fail() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let c1 = factory.commit_value(&k1, 1_000);
+        let c2 = factory.commit_value(&k2, 1_001);
+        let bad_proof = EqualityProof::prove_same_value(&factory, &c1, &c2, &k1, &k2);
+        assert!(!bad_proof.verify_same_value(&factory, &c1, &c2));
+        // A valid proof for other commitments does not transfer
+        let c3 = factory.commit_value(&k2, 1_000);
+        let proof = EqualityProof::prove_same_value(&factory, &c1, &c3, &k1, &k2);
+        assert!(proof.verify_same_value(&factory, &c1, &c3));
+        assert!(!proof.verify_same_value(&factory, &c1, &c2));
+        // The same openings in a different statement get a different nonce, so k1 - k2 is not revealed
+        assert_ne!(proof.R, bad_proof.R);
+    }
+
+    #[test]
+    fn commitment_to_zero() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        // A balanced set of commitments: the excess is a commitment to zero with blinding k1 - k2 - k3
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let k3 = RistrettoSecretKey::random(&mut rng);
+        let input = factory.commit_value(&k1, 1_000);
+        let output = factory.commit_value(&k2, 900);
+        let change = factory.commit_value(&k3, 100);
+        let excess = &(&input - &output) - &change;
+        let blinding = &(&k1 - &k2) - &k3;
+        let proof = factory.prove_commitment_is_zero(&excess, &blinding);
+        assert!(factory.verify_commitment_is_zero(&excess, &proof));
+        assert!(!factory.verify_commitment_is_zero(&input, &proof));
+
+        // A commitment to a non-zero value cannot be proven to be zero with its blinding factor
+        let non_zero = factory.commit_value(&k1, 1);
+        let proof = factory.prove_commitment_is_zero(&non_zero, &k1);
+        assert!(!factory.verify_commitment_is_zero(&non_zero, &proof));
+    }
+}
@@ -26,6 +26,10 @@ use bulletproofs::{
     PedersenGens,
     RangeProof as DalekProof,
 };
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
 use merlin::Transcript;
 
 use crate::{
@@ -47,15 +51,15 @@ pub struct DalekRangeProofService {
     bp_gens: BulletproofGens,
 }
 
-const MASK: usize = 0b111_1000; // Mask for 8,16,32,64; the valid ranges on the Dalek library
 pub const REWIND_PROOF_MESSAGE_LENGTH: usize = 23;
 pub const REWIND_CHECK_MESSAGE: &[u8; 2] = b"TR";
 
 impl DalekRangeProofService {
     /// Create a new RangeProofService. The Dalek library can only generate proofs for ranges between [0; 2^range),
-    /// where valid range values are 8, 16, 32 and 64.
+    /// where valid range values are 8, 16, 32 and 64. Any other range returns an `InitializationError`, and
+    /// attempting to prove a value of \\( 2^{range} \\) or more returns a `ProofConstructionError`.
     pub fn new(range: usize, base: &PedersenCommitmentFactory) -> Result<DalekRangeProofService, RangeProofError> {
-        if range == 0 || (range | MASK != MASK) {
+        if !matches!(range, 8 | 16 | 32 | 64) {
             return Err(RangeProofError::InitializationError);
         }
         let pc_gens = PedersenGens {
@@ -69,6 +73,95 @@ impl DalekRangeProofService {
             bp_gens,
         })
     }
+
+    /// The maximum number of values that the service's generators can support in a single aggregated proof of
+    /// `bit_length` bits per value. Returns zero if `bit_length` exceeds the generator capacity.
+    ///
+    /// This service only constructs single-value proofs, so its generators support an aggregation size of one for
+    /// the service's range.
+    pub fn max_aggregation_size(&self, bit_length: usize) -> usize {
+        if bit_length == 0 || bit_length > self.bp_gens.gens_capacity {
+            return 0;
+        }
+        self.bp_gens.party_capacity
+    }
+
+    /// Commit to `value` using the blinding factor `key` and the service's generators, and construct a range proof
+    /// for it in one step. This guarantees that the proof is made against the returned commitment. An error is
+    /// returned if `value` does not fit in the range of the service.
+    pub fn commit_and_prove(
+        &self,
+        key: &RistrettoSecretKey,
+        value: u64,
+    ) -> Result<(PedersenCommitment, Vec<u8>), RangeProofError> {
+        self.check_value_in_range(value)?;
+        let commitment = self.pc_gens.commit(Scalar::from(value), key.0);
+        let commitment = PedersenCommitment::from_public_key(&RistrettoPublicKey::new_from_pk(commitment));
+        let proof = self.construct_proof(key, value)?;
+        Ok((commitment, proof))
+    }
+
+    /// Verify a commitment and range proof pair, such as one produced by
+    /// [commit_and_prove](DalekRangeProofService::commit_and_prove).
+    pub fn verify_commitment_and_proof(&self, commitment: &PedersenCommitment, proof: &[u8]) -> bool {
+        self.verify_bytes(proof, commitment.as_public_key().compressed())
+    }
+
+    /// Construct a range proof attesting that the value committed to by `key` lies in the range
+    /// `[min_value; min_value + 2^range)`. This is a standard range proof over the offset commitment
+    /// \\( C - m.H = k.G + (v - m).H \\), so an error is returned if `value` is less than `min_value`.
+    ///
+    /// The proof must be checked using [verify_with_min](DalekRangeProofService::verify_with_min) with the same
+    /// `min_value`.
+    pub fn construct_proof_with_min(
+        &self,
+        key: &RistrettoSecretKey,
+        value: u64,
+        min_value: u64,
+    ) -> Result<Vec<u8>, RangeProofError> {
+        let offset_value = value
+            .checked_sub(min_value)
+            .ok_or(RangeProofError::ProofConstructionError)?;
+        self.construct_proof(key, offset_value)
+    }
+
+    /// Verify a proof produced by [construct_proof_with_min](DalekRangeProofService::construct_proof_with_min),
+    /// i.e. that `commitment` holds a value of at least `min_value`. Verification fails if the committed value is less
+    /// than `min_value`.
+    pub fn verify_with_min(&self, proof: &[u8], commitment: &PedersenCommitment, min_value: u64) -> bool {
+        let offset = RistrettoPoint::from(commitment.as_public_key()) - Scalar::from(min_value) * self.pc_gens.B;
+        let offset = RistrettoPublicKey::new_from_pk(offset);
+        self.verify_bytes(proof, offset.compressed())
+    }
+
+    /// Verify several range proofs, each against its own commitment, returning false if any of them is invalid. An
+    /// empty list is trivially valid. The proofs are checked one after another, stopping at the first failure, so this
+    /// is a convenience over calling [verify](RangeProofService::verify) on each proof, not a faster batch check.
+    pub fn verify_all(&self, proofs: &[(&[u8], &PedersenCommitment)]) -> bool {
+        proofs
+            .iter()
+            .all(|(proof, commitment)| self.verify_bytes(proof, commitment.as_public_key().compressed()))
+    }
+
+    /// Verify a serialized proof against a compressed commitment
+    fn verify_bytes(&self, proof: &[u8], commitment: &CompressedRistretto) -> bool {
+        let rp = match DalekProof::from_bytes(proof) {
+            Ok(rp) => rp,
+            Err(_) => return false,
+        };
+        let mut pt = Transcript::new(b"tari");
+        rp.verify_single(&self.bp_gens, &self.pc_gens, &mut pt, commitment, self.range)
+            .is_ok()
+    }
+
+    /// Return an error if `value` does not fit in the `range` bits of the service, i.e. if \\( v \geq 2^{range} \\).
+    /// The Bulletproofs prover does not check this itself and happily produces a proof that can never verify.
+    fn check_value_in_range(&self, value: u64) -> Result<(), RangeProofError> {
+        if self.range < 64 && value >> self.range != 0 {
+            return Err(RangeProofError::ProofConstructionError);
+        }
+        Ok(())
+    }
 }
 
 impl RangeProofService for DalekRangeProofService {
@@ -77,6 +170,7 @@ impl RangeProofService for DalekRangeProofService {
     type Proof = Vec<u8>;
 
     fn construct_proof(&self, key: &RistrettoSecretKey, value: u64) -> Result<Vec<u8>, RangeProofError> {
+        self.check_value_in_range(value)?;
         let mut pt = Transcript::new(b"tari");
         let k = key.0;
         let (proof, _) = DalekProof::prove_single(&self.bp_gens, &self.pc_gens, &mut pt, value, &k, self.range)
@@ -114,6 +208,7 @@ impl RewindableRangeProofService for DalekRangeProofService {
         rewind_blinding_key: &RistrettoSecretKey,
         proof_message: &[u8; REWIND_USER_MESSAGE_LENGTH],
     ) -> Result<Vec<u8>, RangeProofError> {
+        self.check_value_in_range(value)?;
         let mut pt = Transcript::new(b"tari");
         let mut full_proof_message = [0u8; REWIND_PROOF_MESSAGE_LENGTH];
         full_proof_message[0..REWIND_CHECK_MESSAGE.len()].clone_from_slice(REWIND_CHECK_MESSAGE);
@@ -218,6 +313,8 @@ impl RewindableRangeProofService for DalekRangeProofService {
 
 #[cfg(test)]
 mod test {
+    use bulletproofs::RangeProof as DalekProof;
+    use merlin::Transcript;
     use rand::thread_rng;
 
     use crate::{
@@ -260,6 +357,74 @@ mod test {
         assert!(!prover.verify(&proof, &c));
     }
 
+    #[test]
+    fn max_aggregation_size() {
+        let base = PedersenCommitmentFactory::default();
+        let prover = DalekRangeProofService::new(32, &base).unwrap();
+        assert_eq!(prover.max_aggregation_size(32), 1);
+        assert_eq!(prover.max_aggregation_size(8), 1);
+        assert_eq!(prover.max_aggregation_size(64), 0);
+        assert_eq!(prover.max_aggregation_size(0), 0);
+        // Aggregating more values than the generators support fails gracefully
+        let mut rng = thread_rng();
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let mut pt = Transcript::new(b"tari");
+        let result = DalekProof::prove_multiple(&prover.bp_gens, &prover.pc_gens, &mut pt, &[1, 2], &[k1.0, k2.0], 32);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn commit_and_prove() {
+        let base = PedersenCommitmentFactory::default();
+        let prover = DalekRangeProofService::new(16, &base).unwrap();
+        let mut rng = thread_rng();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let (c, proof) = prover.commit_and_prove(&k, 65_535).unwrap();
+        assert_eq!(c, base.commit_value(&k, 65_535));
+        assert!(prover.verify_commitment_and_proof(&c, &proof));
+        assert!(!prover.verify_commitment_and_proof(&base.commit_value(&k, 65_534), &proof));
+        // Values that overflow the range cannot be proven
+        assert_eq!(
+            prover.commit_and_prove(&k, 65_536),
+            Err(RangeProofError::ProofConstructionError)
+        );
+        // The full 64-bit range is supported
+        let prover = DalekRangeProofService::new(64, &base).unwrap();
+        let (c, proof) = prover.commit_and_prove(&k, u64::MAX).unwrap();
+        assert!(prover.verify_commitment_and_proof(&c, &proof));
+    }
+
+    #[test]
+    fn create_and_verify_proof_with_min() {
+        let base = PedersenCommitmentFactory::default();
+        let prover = DalekRangeProofService::new(32, &base).unwrap();
+        let mut rng = thread_rng();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let min = 1_000;
+        // value == min
+        let c = base.commit_value(&k, min);
+        let proof = prover.construct_proof_with_min(&k, min, min).unwrap();
+        assert!(prover.verify_with_min(&proof, &c, min));
+        // value > min
+        let c = base.commit_value(&k, min + 42);
+        let proof = prover.construct_proof_with_min(&k, min + 42, min).unwrap();
+        assert!(prover.verify_with_min(&proof, &c, min));
+        // The proof is bound to the minimum it was created for
+        assert!(!prover.verify_with_min(&proof, &c, min + 1));
+        assert!(!prover.verify(&proof, &c));
+        // value < min
+        assert_eq!(
+            prover.construct_proof_with_min(&k, min - 1, min),
+            Err(RangeProofError::ProofConstructionError)
+        );
+        // A proof for a value below the minimum, created with a lower minimum, does not pass the higher minimum
+        let c = base.commit_value(&k, min - 1);
+        let proof = prover.construct_proof_with_min(&k, min - 1, 0).unwrap();
+        assert!(prover.verify_with_min(&proof, &c, 0));
+        assert!(!prover.verify_with_min(&proof, &c, min));
+    }
+
     #[test]
     fn create_and_rewind_proof() {
         let base = PedersenCommitmentFactory::default();
@@ -325,10 +490,15 @@ mod test {
     #[test]
     fn non_power_of_two_range() {
         let base = PedersenCommitmentFactory::default();
-        assert!(matches!(
-            DalekRangeProofService::new(10, &base),
-            Err(RangeProofError::InitializationError)
-        ));
+        for range in &[0, 10, 24, 40, 48, 56, 72, 120, 128] {
+            assert!(matches!(
+                DalekRangeProofService::new(*range, &base),
+                Err(RangeProofError::InitializationError)
+            ));
+        }
+        for range in &[8, 16, 32, 64] {
+            assert!(DalekRangeProofService::new(*range, &base).is_ok());
+        }
     }
 
     #[test]
@@ -346,12 +516,54 @@ mod test {
         let proof = prover.construct_proof(&k, in_range).unwrap();
         assert!(prover.verify(&proof, &c));
         // Test value out of range
-        let proof = prover.construct_proof(&k, out_of_range).unwrap();
-        // Test every single value from 0..255 - the proof should fail for every one
-        for i in 0..257 {
-            let v = RistrettoSecretKey::from(i);
-            let c = commitment_factory.commit(&k, &v);
-            assert!(!prover.verify(&proof, &c));
+        assert_eq!(
+            prover.construct_proof(&k, out_of_range),
+            Err(RangeProofError::ProofConstructionError)
+        );
+        assert!(prover.commit_and_prove(&k, out_of_range).is_err());
+    }
+
+    #[test]
+    fn range_proof_bit_lengths() {
+        let mut rng = thread_rng();
+        let base = PedersenCommitmentFactory::default();
+        for &bit_length in &[8usize, 16, 32, 64] {
+            let prover = DalekRangeProofService::new(bit_length, &base).unwrap();
+            let k = RistrettoSecretKey::random(&mut rng);
+            // The largest value that fits in the range must prove and verify
+            let max_value = u64::MAX >> (64 - bit_length);
+            let proof = prover.construct_proof(&k, max_value).unwrap();
+            assert!(prover.verify(&proof, &base.commit_value(&k, max_value)));
+            // There is no value above the range of a 64-bit proof
+            if bit_length < 64 {
+                assert!(prover.construct_proof(&k, max_value + 1).is_err());
+            }
         }
     }
+
+    #[test]
+    fn verify_all() {
+        let mut rng = thread_rng();
+        let base = PedersenCommitmentFactory::default();
+        let prover = DalekRangeProofService::new(32, &base).unwrap();
+        let batch = (0..4u64)
+            .map(|i| {
+                let k = RistrettoSecretKey::random(&mut rng);
+                let v = 1000 * i + 7;
+                (base.commit_value(&k, v), prover.construct_proof(&k, v).unwrap())
+            })
+            .collect::<Vec<_>>();
+        let mut proofs = batch.iter().map(|(c, p)| (p.as_slice(), c)).collect::<Vec<_>>();
+        assert!(prover.verify_all(&proofs));
+        assert!(prover.verify_all(&[]));
+        // Swap two commitments so that two of the proofs are checked against the wrong commitment
+        proofs[0].1 = &batch[1].0;
+        proofs[1].1 = &batch[0].0;
+        assert!(!prover.verify_all(&proofs));
+        // A single invalid proof fails the whole check
+        let garbage = vec![0u8; batch[2].1.len()];
+        let mut proofs = batch.iter().map(|(c, p)| (p.as_slice(), c)).collect::<Vec<_>>();
+        proofs[2].0 = &garbage[..];
+        assert!(!prover.verify_all(&proofs));
+    }
 }
@@ -0,0 +1,214 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Designated verifier signatures
+//!
+//! A designated verifier signature convinces one chosen verifier that a message came from the signer, but nobody
+//! else. The verifier holds a secret key that lets them produce signatures that are indistinguishable from the
+//! signer's, so a signature proves nothing to a third party, which gives the signer deniability.
+//!
+//! The signature is a Schnorr-style OR-proof showing knowledge of the signer's secret key, \\( x_s \\), _or_ the
+//! verifier's secret key, \\( x_v \\). The signer proves the first branch and simulates the second. The
+//! Fiat-Shamir challenge is
+//! $$
+//!   e = H(S || P_s || P_v || R_s || R_v || m)
+//! $$
+//! where \\( S = x_s.P_v = x_v.P_s \\) is the Diffie-Hellman shared secret of the signer and the verifier. Only they
+//! can calculate _S_, so only the signer and the designated verifier can check the signature, and the verifier knows
+//! that they did not make it themselves.
+//!
+//! The signature consists of the branch challenges and responses, \\( (e_s, s_s, e_v, s_v) \\). A verifier
+//! recalculates \\( R_s = s_s.G - e_s.P_s \\) and \\( R_v = s_v.G - e_v.P_v \\), and accepts if
+//! \\( e_s + e_v = e \\).
+
+use blake2::Blake2b;
+use digest::Digest;
+use tari_utilities::ByteArray;
+
+use crate::{
+    hash::blake2::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey},
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+};
+
+const DV_CHALLENGE_PERSONA: &[u8] = b"tari.dv_sig";
+const DV_NONCE_LABEL: &[u8] = b"tari.dv_sig.nonce";
+
+/// A signature that only the designated verifier can check, created with [sign_dv] and checked with [verify_dv]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesignatedVerifierSignature {
+    e_signer: RistrettoSecretKey,
+    s_signer: RistrettoSecretKey,
+    e_verifier: RistrettoSecretKey,
+    s_verifier: RistrettoSecretKey,
+}
+
+/// Sign `message` with `signer_secret` so that only the holder of the secret key for `verifier_public` can verify
+/// it. The random values of the proof are derived deterministically from the signer's secret key, the verifier's
+/// public key and the message.
+#[allow(non_snake_case)]
+pub fn sign_dv(
+    signer_secret: &RistrettoSecretKey,
+    verifier_public: &RistrettoPublicKey,
+    message: &[u8],
+) -> DesignatedVerifierSignature {
+    let signer_public = RistrettoPublicKey::from_secret_key(signer_secret);
+    let shared = RistrettoPublicKey::shared_secret(signer_secret, verifier_public);
+    // Simulate the verifier's branch
+    let e_verifier = nonce_scalar(signer_secret, verifier_public, message, 0);
+    let s_verifier = nonce_scalar(signer_secret, verifier_public, message, 1);
+    let R_v = &RistrettoPublicKey::from_secret_key(&s_verifier) - &(&e_verifier * verifier_public);
+    // Prove the signer's branch
+    let r = nonce_scalar(signer_secret, verifier_public, message, 2);
+    let R_s = RistrettoPublicKey::from_secret_key(&r);
+    let e = challenge(&shared, &signer_public, verifier_public, &R_s, &R_v, message);
+    let e_signer = &e - &e_verifier;
+    let s_signer = &r + &(&e_signer * signer_secret);
+    DesignatedVerifierSignature {
+        e_signer,
+        s_signer,
+        e_verifier,
+        s_verifier,
+    }
+}
+
+/// Returns true if `signature` is a valid designated verifier signature on `message` by `signer_public`, for the
+/// verifier with secret key `verifier_secret`.
+///
+/// A valid signature was made by the signer _or_ by the verifier, e.g. with [simulate_dv]. The verifier knows
+/// whether they made it themselves, but nobody else can tell the two apart.
+#[allow(non_snake_case)]
+pub fn verify_dv(
+    signer_public: &RistrettoPublicKey,
+    verifier_secret: &RistrettoSecretKey,
+    message: &[u8],
+    signature: &DesignatedVerifierSignature,
+) -> bool {
+    let verifier_public = RistrettoPublicKey::from_secret_key(verifier_secret);
+    let shared = RistrettoPublicKey::shared_secret(verifier_secret, signer_public);
+    let R_s = &RistrettoPublicKey::from_secret_key(&signature.s_signer) - &(&signature.e_signer * signer_public);
+    let R_v = &RistrettoPublicKey::from_secret_key(&signature.s_verifier) - &(&signature.e_verifier * &verifier_public);
+    let e = challenge(&shared, signer_public, &verifier_public, &R_s, &R_v, message);
+    e == &signature.e_signer + &signature.e_verifier
+}
+
+/// Produce a signature on `message`, apparently by `signer_public`, using the verifier's secret key. The result is
+/// accepted by [verify_dv] and is indistinguishable from a real signature to anyone but the verifier, which is what
+/// makes designated verifier signatures deniable.
+#[allow(non_snake_case)]
+pub fn simulate_dv(
+    verifier_secret: &RistrettoSecretKey,
+    signer_public: &RistrettoPublicKey,
+    message: &[u8],
+) -> DesignatedVerifierSignature {
+    let verifier_public = RistrettoPublicKey::from_secret_key(verifier_secret);
+    let shared = RistrettoPublicKey::shared_secret(verifier_secret, signer_public);
+    // Simulate the signer's branch
+    let e_signer = nonce_scalar(verifier_secret, signer_public, message, 0);
+    let s_signer = nonce_scalar(verifier_secret, signer_public, message, 1);
+    let R_s = &RistrettoPublicKey::from_secret_key(&s_signer) - &(&e_signer * signer_public);
+    // Prove the verifier's branch
+    let r = nonce_scalar(verifier_secret, signer_public, message, 2);
+    let R_v = RistrettoPublicKey::from_secret_key(&r);
+    let e = challenge(&shared, signer_public, &verifier_public, &R_s, &R_v, message);
+    let e_verifier = &e - &e_signer;
+    let s_verifier = &r + &(&e_verifier * verifier_secret);
+    DesignatedVerifierSignature {
+        e_signer,
+        s_signer,
+        e_verifier,
+        s_verifier,
+    }
+}
+
+/// Derive the `index`th random value of a proof from the prover's secret key, the other party's public key and the
+/// message
+fn nonce_scalar(
+    secret: &RistrettoSecretKey,
+    other_public: &RistrettoPublicKey,
+    message: &[u8],
+    index: u8,
+) -> RistrettoSecretKey {
+    let hash = Blake2b::new()
+        .chain(DV_NONCE_LABEL)
+        .chain(secret.as_bytes())
+        .chain(other_public.as_bytes())
+        .chain(message)
+        .chain([index])
+        .finalize();
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&hash);
+    RistrettoSecretKey::from_uniform_bytes(&bytes)
+}
+
+#[allow(non_snake_case)]
+fn challenge(
+    shared: &RistrettoPublicKey,
+    signer_public: &RistrettoPublicKey,
+    verifier_public: &RistrettoPublicKey,
+    R_s: &RistrettoPublicKey,
+    R_v: &RistrettoPublicKey,
+    message: &[u8],
+) -> RistrettoSecretKey {
+    let hash = Blake256::with_params(&[], &[], DV_CHALLENGE_PERSONA)
+        .chain(shared.as_bytes())
+        .chain(signer_public.as_bytes())
+        .chain(verifier_public.as_bytes())
+        .chain(R_s.as_bytes())
+        .chain(R_v.as_bytes())
+        .chain(message)
+        .finalize();
+    RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ristretto::test_common::get_keypair;
+
+    #[test]
+    fn only_the_designated_verifier_accepts() {
+        let (x_s, p_s) = get_keypair();
+        let (x_v, p_v) = get_keypair();
+        let (x_other, _) = get_keypair();
+        let sig = sign_dv(&x_s, &p_v, b"For your eyes only");
+        assert!(verify_dv(&p_s, &x_v, b"For your eyes only", &sig));
+        assert!(!verify_dv(&p_s, &x_v, b"For everyone", &sig));
+        // Another verifier cannot check the signature
+        assert!(!verify_dv(&p_s, &x_other, b"For your eyes only", &sig));
+        // The signature is bound to the signer
+        let (_, p_wrong) = get_keypair();
+        assert!(!verify_dv(&p_wrong, &x_v, b"For your eyes only", &sig));
+    }
+
+    #[test]
+    fn verifier_can_simulate_signatures() {
+        let (x_s, p_s) = get_keypair();
+        let (x_v, p_v) = get_keypair();
+        let forged = simulate_dv(&x_v, &p_s, b"I never said that");
+        assert!(verify_dv(&p_s, &x_v, b"I never said that", &forged));
+        // The real signature differs, but verifies the same way
+        let real = sign_dv(&x_s, &p_v, b"I never said that");
+        assert_ne!(real, forged);
+        assert!(verify_dv(&p_s, &x_v, b"I never said that", &real));
+    }
+}
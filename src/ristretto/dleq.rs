@@ -0,0 +1,157 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Discrete log equality (DLEQ) proofs on Ristretto
+//!
+//! A `DleqProof` convinces a verifier that two public keys, \\( P_1 = x.B_1 \\) and \\( P_2 = x.B_2 \\), share the
+//! same discrete log, _x_, with respect to two different bases, without revealing _x_.
+//!
+//! The prover calculates \\( R_1 = r.B_1 \\), \\( R_2 = r.B_2 \\), the Fiat-Shamir challenge
+//! \\( e = H(B_1 || P_1 || B_2 || P_2 || R_1 || R_2) \\), and \\( s = r + e.x \\). A verifier accepts if
+//! \\( s.B_1 = R_1 + e.P_1 \\) and \\( s.B_2 = R_2 + e.P_2 \\).
+
+use blake2::Blake2b;
+use curve25519_dalek::scalar::Scalar;
+use digest::Digest;
+use tari_utilities::ByteArray;
+
+use crate::{
+    hash::blake2::Blake256,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+};
+
+const DLEQ_CHALLENGE_PERSONA: &[u8] = b"tari.dleq.chall";
+const DLEQ_NONCE_LABEL: &[u8] = b"tari.dleq.nonce";
+
+/// A non-interactive proof that two public keys share the same discrete log under different bases
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DleqProof {
+    R1: RistrettoPublicKey,
+    R2: RistrettoPublicKey,
+    s: RistrettoSecretKey,
+}
+
+impl DleqProof {
+    /// Prove that `secret.base1` and `secret.base2` share the discrete log `secret`.
+    ///
+    /// The nonce is derived deterministically from the secret and the statement being proven, so there is no need
+    /// for the caller to supply one, and no risk of it being reused across different statements.
+    #[allow(non_snake_case)]
+    pub fn prove(secret: &RistrettoSecretKey, base1: &RistrettoPublicKey, base2: &RistrettoPublicKey) -> Self {
+        let P1 = secret * base1;
+        let P2 = secret * base2;
+        let r = DleqProof::nonce(secret, base1, base2);
+        let R1 = &r * base1;
+        let R2 = &r * base2;
+        let e = DleqProof::challenge(base1, &P1, base2, &P2, &R1, &R2);
+        let s = &r + &(&e * secret);
+        DleqProof { R1, R2, s }
+    }
+
+    /// Returns true if this proof shows that `p1 = x.base1` and `p2 = x.base2` for the same (unknown) _x_.
+    pub fn verify(
+        &self,
+        p1: &RistrettoPublicKey,
+        base1: &RistrettoPublicKey,
+        p2: &RistrettoPublicKey,
+        base2: &RistrettoPublicKey,
+    ) -> bool {
+        let e = DleqProof::challenge(base1, p1, base2, p2, &self.R1, &self.R2);
+        let lhs1 = &self.s * base1;
+        let rhs1 = &self.R1 + &(&e * p1);
+        let lhs2 = &self.s * base2;
+        let rhs2 = &self.R2 + &(&e * p2);
+        lhs1 == rhs1 && lhs2 == rhs2
+    }
+
+    fn nonce(
+        secret: &RistrettoSecretKey,
+        base1: &RistrettoPublicKey,
+        base2: &RistrettoPublicKey,
+    ) -> RistrettoSecretKey {
+        let hash = Blake2b::new()
+            .chain(DLEQ_NONCE_LABEL)
+            .chain(secret.as_bytes())
+            .chain(base1.as_bytes())
+            .chain(base2.as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash);
+        RistrettoSecretKey(Scalar::from_bytes_mod_order_wide(&bytes))
+    }
+
+    #[allow(non_snake_case)]
+    fn challenge(
+        base1: &RistrettoPublicKey,
+        P1: &RistrettoPublicKey,
+        base2: &RistrettoPublicKey,
+        P2: &RistrettoPublicKey,
+        R1: &RistrettoPublicKey,
+        R2: &RistrettoPublicKey,
+    ) -> RistrettoSecretKey {
+        let hash = Blake256::with_params(&[], &[], DLEQ_CHALLENGE_PERSONA)
+            .chain(base1.as_bytes())
+            .chain(P1.as_bytes())
+            .chain(base2.as_bytes())
+            .chain(P2.as_bytes())
+            .chain(R1.as_bytes())
+            .chain(R2.as_bytes())
+            .finalize();
+        RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        keys::{PublicKey, SecretKey},
+        ristretto::{dleq::DleqProof, RistrettoPublicKey, RistrettoSecretKey},
+    };
+
+    #[test]
+    fn matching_exponents_verify() {
+        let mut rng = rand::thread_rng();
+        let x = RistrettoSecretKey::random(&mut rng);
+        let (_, base1) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, base2) = RistrettoPublicKey::random_keypair(&mut rng);
+        let p1 = &x * &base1;
+        let p2 = &x * &base2;
+        let proof = DleqProof::prove(&x, &base1, &base2);
+        assert!(proof.verify(&p1, &base1, &p2, &base2));
+        // The bases are bound to the proof
+        assert!(!proof.verify(&p2, &base2, &p1, &base1));
+    }
+
+    #[test]
+    fn mismatched_exponents_fail() {
+        let mut rng = rand::thread_rng();
+        let x = RistrettoSecretKey::random(&mut rng);
+        let y = RistrettoSecretKey::random(&mut rng);
+        let (_, base1) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, base2) = RistrettoPublicKey::random_keypair(&mut rng);
+        let p1 = &x * &base1;
+        let p2 = &y * &base2;
+        let proof = DleqProof::prove(&x, &base1, &base2);
+        assert!(!proof.verify(&p1, &base1, &p2, &base2));
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Encryption of the value and blinding factor behind a commitment to the recipient of an output
+//!
+//! The sender and recipient agree on an ECDH shared secret, e.g. with
+//! [shared_secret](crate::keys::DiffieHellmanSharedSecret::shared_secret), and the sender encrypts the committed value
+//! and mask under a key derived from it using ChaCha20-Poly1305. Since the encryption is authenticated, decrypting with
+//! the wrong shared secret, or a tampered ciphertext, fails instead of producing a garbage value.
+
+use std::convert::TryFrom;
+
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305,
+    Key,
+    Nonce,
+};
+use digest::Digest;
+use tari_utilities::{ByteArray, ByteArrayError};
+use zeroize::Zeroize;
+
+use crate::{
+    hash::blake2::Blake256,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+};
+
+const ENCRYPTION_KEY_PERSONA: &[u8] = b"tari.enc_value";
+const NONCE_PERSONA: &[u8] = b"tari.enc_nonce";
+const NONCE_SIZE: usize = 12;
+const PLAINTEXT_SIZE: usize = 8 + 32;
+const TAG_SIZE: usize = 16;
+/// The size of an encrypted value: the nonce, the ciphertext of the value and mask, and the authentication tag
+pub const ENCRYPTED_VALUE_SIZE: usize = NONCE_SIZE + PLAINTEXT_SIZE + TAG_SIZE;
+
+/// A value and mask encrypted to the holder of a shared secret, serialized as the 12-byte nonce followed by the
+/// ciphertext and tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedValue(Vec<u8>);
+
+impl EncryptedValue {
+    /// Encrypt `value` and `mask` under a key derived from `shared_secret`.
+    ///
+    /// The nonce is derived from the encryption key and the whole plaintext, i.e. the value and the mask, rather than
+    /// drawn from an RNG. A nonce is therefore only repeated for an identical plaintext under the same key, which
+    /// reveals nothing beyond the fact that the same value and mask were encrypted twice. In particular, encrypting
+    /// different values with the same mask and shared secret uses different nonces. The nonce reveals nothing to
+    /// anyone without the key.
+    pub fn encrypt(shared_secret: &RistrettoPublicKey, value: u64, mask: &RistrettoSecretKey) -> Self {
+        let mut key = encryption_key(shared_secret);
+        let nonce = Blake256::with_params(&[], &[], NONCE_PERSONA)
+            .chain(&key)
+            .chain(value.to_le_bytes())
+            .chain(mask.as_bytes())
+            .finalize();
+        let nonce = &nonce[..NONCE_SIZE];
+        let mut plaintext = [0u8; PLAINTEXT_SIZE];
+        plaintext[..8].copy_from_slice(&value.to_le_bytes());
+        plaintext[8..].copy_from_slice(mask.as_bytes());
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(nonce), &plaintext[..])
+            .expect("Encrypting a fixed-size buffer cannot fail");
+        key.zeroize();
+        plaintext.zeroize();
+        let mut bytes = Vec::with_capacity(ENCRYPTED_VALUE_SIZE);
+        bytes.extend_from_slice(nonce);
+        bytes.extend_from_slice(&ciphertext);
+        EncryptedValue(bytes)
+    }
+
+    /// Decrypt the value and mask with a key derived from `shared_secret`. Returns `None` if the shared secret is
+    /// wrong, the ciphertext has been tampered with, or the decrypted mask is not a canonical scalar.
+    pub fn decrypt(&self, shared_secret: &RistrettoPublicKey) -> Option<(u64, RistrettoSecretKey)> {
+        let mut key = encryption_key(shared_secret);
+        let (nonce, ciphertext) = self.0.split_at(NONCE_SIZE);
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key)).decrypt(Nonce::from_slice(nonce), ciphertext);
+        key.zeroize();
+        let mut plaintext = plaintext.ok()?;
+        let mut value = [0u8; 8];
+        value.copy_from_slice(&plaintext[..8]);
+        let mask = RistrettoSecretKey::try_from(&plaintext[8..]);
+        plaintext.zeroize();
+        Some((u64::from_le_bytes(value), mask.ok()?))
+    }
+}
+
+/// Derive the symmetric encryption key from the ECDH shared secret
+fn encryption_key(shared_secret: &RistrettoPublicKey) -> [u8; 32] {
+    let hash = Blake256::with_params(&[], &[], ENCRYPTION_KEY_PERSONA)
+        .chain(shared_secret.as_bytes())
+        .finalize();
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hash);
+    key
+}
+
+impl ByteArray for EncryptedValue {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        if bytes.len() != ENCRYPTED_VALUE_SIZE {
+            return Err(ByteArrayError::IncorrectLength);
+        }
+        Ok(EncryptedValue(bytes.to_vec()))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{DiffieHellmanSharedSecret, PublicKey, SecretKey},
+        ristretto::pedersen::PedersenCommitmentFactory,
+    };
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut rng = rand::thread_rng();
+        let (a, pub_a) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (b, pub_b) = RistrettoPublicKey::random_keypair(&mut rng);
+        let factory = PedersenCommitmentFactory::default();
+        let mask = RistrettoSecretKey::random(&mut rng);
+        let commitment = factory.commit_value(&mask, 1_000_000);
+
+        let sender_secret = RistrettoPublicKey::shared_secret(&a, &pub_b);
+        let encrypted = EncryptedValue::encrypt(&sender_secret, 1_000_000, &mask);
+        assert_eq!(encrypted.as_bytes().len(), ENCRYPTED_VALUE_SIZE);
+
+        let recipient_secret = RistrettoPublicKey::shared_secret(&b, &pub_a);
+        let (value, decrypted_mask) = encrypted.decrypt(&recipient_secret).unwrap();
+        assert_eq!(value, 1_000_000);
+        assert!(factory.open_value(&decrypted_mask, value, &commitment));
+
+        let (_, wrong) = RistrettoPublicKey::random_keypair(&mut rng);
+        assert!(encrypted.decrypt(&wrong).is_none());
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut rng = rand::thread_rng();
+        let (_, shared_secret) = RistrettoPublicKey::random_keypair(&mut rng);
+        let mask = RistrettoSecretKey::random(&mut rng);
+        let encrypted = EncryptedValue::encrypt(&shared_secret, 42, &mask);
+        let mut bytes = encrypted.as_bytes().to_vec();
+        bytes[NONCE_SIZE] ^= 1;
+        let tampered = EncryptedValue::from_bytes(&bytes).unwrap();
+        assert!(tampered.decrypt(&shared_secret).is_none());
+        assert!(EncryptedValue::from_bytes(&bytes[1..]).is_err());
+        let copy = EncryptedValue::from_bytes(encrypted.as_bytes()).unwrap();
+        assert_eq!(copy.decrypt(&shared_secret), Some((42, mask)));
+    }
+
+    #[test]
+    fn nonce_depends_on_value() {
+        let mut rng = rand::thread_rng();
+        let (_, shared_secret) = RistrettoPublicKey::random_keypair(&mut rng);
+        let mask = RistrettoSecretKey::random(&mut rng);
+        let first = EncryptedValue::encrypt(&shared_secret, 42, &mask);
+        let second = EncryptedValue::encrypt(&shared_secret, 43, &mask);
+        assert_ne!(first.as_bytes()[..NONCE_SIZE], second.as_bytes()[..NONCE_SIZE]);
+        // Encryption is deterministic for the same plaintext
+        assert_eq!(first, EncryptedValue::encrypt(&shared_secret, 42, &mask));
+        assert_eq!(second.decrypt(&shared_secret), Some((43, mask)));
+    }
+}
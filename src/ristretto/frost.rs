@@ -0,0 +1,499 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Threshold Schnorr signatures (FROST)
+//!
+//! FROST lets any _t_ of _n_ participants produce a single Schnorr signature for a shared group public key, _Y_. The
+//! signature is an ordinary [RistrettoSchnorr] with the challenge \\( c = H(R || Y || m) \\) of
+//! [sign_message](RistrettoSchnorr::sign_message), so it is checked with
+//! [verify_message](RistrettoSchnorr::verify_message) and cannot be told apart from a single-signer signature.
+//!
+//! This first implementation uses a trusted dealer for key generation. The dealer splits the group secret key, _s_,
+//! with a random polynomial _f_ of degree \\( t - 1 \\), where \\( f(0) = s \\), and hands participant _i_ the share
+//! \\( s_i = f(i) \\). The dealer learns the group secret and must be trusted to forget it.
+//!
+//! Signing takes two rounds:
+//!
+//! 1. Each signer, _i_, draws a hiding and a binding nonce, \\( (d_i, e_i) \\), with [generate_nonces] and publishes
+//!    the [NonceCommitment] \\( (D_i, E_i) = (d_i.G, e_i.G) \\).
+//! 2. Once the commitments of all signers are known, each signer computes the binding factor
+//!    \\( \rho_i = H(i || Y || B || m) \\), where _B_ is the list of commitments, the group commitment
+//!    \\( R = \sum D_j + \rho_j.E_j \\), the challenge _c_, and their [SignatureShare] with [sign]:
+//!    $$ z_i = d_i + \rho_i e_i + \lambda_i s_i c $$
+//!    where \\( \lambda_i \\) is the Lagrange coefficient of _i_ for the set of signers.
+//!
+//! Any party can then [aggregate] the shares into the signature \\( (R, \sum z_i) \\).
+//!
+//! Nonces must never be reused. [sign] takes the [SigningNonces] by value so that they are consumed when used.
+
+use blake2::Blake2b;
+use digest::Digest;
+use rand::{CryptoRng, RngCore};
+use tari_utilities::ByteArray;
+use thiserror::Error;
+
+use crate::{
+    hash::blake2::Blake256,
+    keys::{PublicKey, SecretKey},
+    ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+};
+
+const FROST_BINDING_LABEL: &[u8] = b"tari.frost.binding";
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum FrostError {
+    #[error("The threshold must be at least 1 and no more than the number of participants")]
+    InvalidThreshold,
+    #[error("Participant index {0} is not valid")]
+    InvalidParticipantIndex(u16),
+    #[error("Participant {0} appears more than once in the signing set")]
+    DuplicateParticipant(u16),
+    #[error("Fewer signers than the threshold took part")]
+    NotEnoughSigners,
+    #[error("There is no nonce commitment for participant {0}")]
+    MissingCommitment(u16),
+    #[error("The signing nonces do not match the signer's published commitment")]
+    NonceMismatch,
+    #[error("The signature share of participant {0} is invalid")]
+    InvalidSignatureShare(u16),
+    #[error("The aggregate signature did not verify")]
+    InvalidAggregateSignature,
+}
+
+/// A participant's share of the group secret key, as issued by [trusted_dealer_keygen]
+#[derive(Clone, Debug)]
+pub struct KeyShare {
+    index: u16,
+    threshold: u16,
+    secret_share: RistrettoSecretKey,
+    group_public_key: RistrettoPublicKey,
+}
+
+impl KeyShare {
+    /// The participant's index, _i_, which is never zero
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// The number of participants needed to sign
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// The participant's public verification share, \\( Y_i = s_i.G \\)
+    pub fn public_share(&self) -> RistrettoPublicKey {
+        RistrettoPublicKey::from_secret_key(&self.secret_share)
+    }
+
+    /// The group public key, _Y_, that aggregate signatures verify against
+    pub fn group_public_key(&self) -> &RistrettoPublicKey {
+        &self.group_public_key
+    }
+}
+
+/// The public commitment, \\( (D_i, E_i) \\), to a signer's nonces, which is sent to the other signers in the first
+/// round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonceCommitment {
+    index: u16,
+    hiding: RistrettoPublicKey,
+    binding: RistrettoPublicKey,
+}
+
+impl NonceCommitment {
+    /// The index of the participant that made this commitment
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// A signer's secret nonces, \\( (d_i, e_i) \\). These are consumed by [sign] and must never be used twice.
+pub struct SigningNonces {
+    hiding: RistrettoSecretKey,
+    binding: RistrettoSecretKey,
+    commitment: NonceCommitment,
+}
+
+/// A signer's share, \\( z_i \\), of the aggregate signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureShare {
+    index: u16,
+    z: RistrettoSecretKey,
+}
+
+impl SignatureShare {
+    /// The index of the participant that made this share
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// Split `secret` into `participants` shares, any `threshold` of which can sign for the group public key
+/// \\( Y = s.G \\). Participants are numbered from 1.
+pub fn trusted_dealer_keygen<R: RngCore + CryptoRng>(
+    secret: &RistrettoSecretKey,
+    threshold: u16,
+    participants: u16,
+    rng: &mut R,
+) -> Result<Vec<KeyShare>, FrostError> {
+    if threshold == 0 || threshold > participants {
+        return Err(FrostError::InvalidThreshold);
+    }
+    let group_public_key = RistrettoPublicKey::from_secret_key(secret);
+    let mut coefficients = vec![secret.clone()];
+    coefficients.extend((1..threshold).map(|_| RistrettoSecretKey::random(rng)));
+    let shares = (1..=participants)
+        .map(|index| {
+            // Evaluate f(i) using Horner's method
+            let x = RistrettoSecretKey::from(u64::from(index));
+            let secret_share = coefficients
+                .iter()
+                .rev()
+                .fold(RistrettoSecretKey::default(), |acc, a| &(&acc * &x) + a);
+            KeyShare {
+                index,
+                threshold,
+                secret_share,
+                group_public_key: group_public_key.clone(),
+            }
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Draw a fresh pair of signing nonces for `key_share`, returning them along with the commitment to publish in the
+/// first round of signing
+pub fn generate_nonces<R: RngCore + CryptoRng>(key_share: &KeyShare, rng: &mut R) -> (SigningNonces, NonceCommitment) {
+    let hiding = RistrettoSecretKey::random_non_zero(rng);
+    let binding = RistrettoSecretKey::random_non_zero(rng);
+    let commitment = NonceCommitment {
+        index: key_share.index,
+        hiding: RistrettoPublicKey::from_secret_key(&hiding),
+        binding: RistrettoPublicKey::from_secret_key(&binding),
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitment: commitment.clone(),
+    };
+    (nonces, commitment)
+}
+
+/// Produce this signer's share of the signature over `message`. `commitments` must hold the nonce commitment of every
+/// participant in the signing set, including this one, and must be the same for all signers.
+// The nonces are taken by value so that they cannot be used again
+#[allow(clippy::needless_pass_by_value)]
+pub fn sign(
+    key_share: &KeyShare,
+    nonces: SigningNonces,
+    commitments: &[NonceCommitment],
+    message: &[u8],
+) -> Result<SignatureShare, FrostError> {
+    let commitments = signing_set(commitments, key_share.threshold)?;
+    let own = commitments
+        .iter()
+        .find(|c| c.index == key_share.index)
+        .ok_or(FrostError::MissingCommitment(key_share.index))?;
+    if *own != nonces.commitment {
+        return Err(FrostError::NonceMismatch);
+    }
+    let (binding_factors, group_commitment) = group_commitment(&commitments, &key_share.group_public_key, message);
+    let c = RistrettoSchnorr::message_challenge::<Blake256>(&group_commitment, &key_share.group_public_key, message);
+    let rho = binding_factor_of(&commitments, &binding_factors, key_share.index);
+    let lambda = lagrange_coefficient(&commitments, key_share.index);
+    let z = &(&nonces.hiding + &(&nonces.binding * rho)) + &(&lambda * &(&key_share.secret_share * &c));
+    Ok(SignatureShare {
+        index: key_share.index,
+        z,
+    })
+}
+
+/// Returns true if `share` is a valid signature share for the participant with public verification share
+/// `public_share`, i.e. if \\( z_i.G = D_i + \rho_i.E_i + (c \lambda_i).Y_i \\). This lets an aggregator identify a
+/// misbehaving signer when [aggregate] fails.
+pub fn verify_signature_share(
+    share: &SignatureShare,
+    public_share: &RistrettoPublicKey,
+    group_public_key: &RistrettoPublicKey,
+    commitments: &[NonceCommitment],
+    message: &[u8],
+) -> bool {
+    let commitments = match signing_set(commitments, 1) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+    let commitment = match commitments.iter().find(|c| c.index == share.index) {
+        Some(c) => c,
+        None => return false,
+    };
+    let (binding_factors, group_commitment) = group_commitment(&commitments, group_public_key, message);
+    let c = RistrettoSchnorr::message_challenge::<Blake256>(&group_commitment, group_public_key, message);
+    let rho = binding_factor_of(&commitments, &binding_factors, share.index);
+    let lambda = lagrange_coefficient(&commitments, share.index);
+    let lhs = RistrettoPublicKey::from_secret_key(&share.z);
+    let rhs = &(&commitment.hiding + &(rho * &commitment.binding)) + &(&(&c * &lambda) * public_share);
+    lhs == rhs
+}
+
+/// Combine the signature shares of every participant in `commitments` into a signature over `message` for
+/// `group_public_key`. The result is checked before it is returned.
+pub fn aggregate(
+    commitments: &[NonceCommitment],
+    shares: &[SignatureShare],
+    group_public_key: &RistrettoPublicKey,
+    message: &[u8],
+) -> Result<RistrettoSchnorr, FrostError> {
+    let commitments = signing_set(commitments, 1)?;
+    if shares.len() != commitments.len() {
+        return Err(FrostError::NotEnoughSigners);
+    }
+    if let Some(c) = commitments.iter().find(|c| !shares.iter().any(|s| s.index == c.index)) {
+        return Err(FrostError::MissingCommitment(c.index));
+    }
+    let (_, group_commitment) = group_commitment(&commitments, group_public_key, message);
+    let z = shares.iter().fold(RistrettoSecretKey::default(), |acc, s| &acc + &s.z);
+    let signature = RistrettoSchnorr::new(group_commitment, z);
+    if !signature.verify_message(group_public_key, message) {
+        return Err(FrostError::InvalidAggregateSignature);
+    }
+    Ok(signature)
+}
+
+/// Check the commitments of a signing set and return them sorted by participant index
+fn signing_set(commitments: &[NonceCommitment], threshold: u16) -> Result<Vec<NonceCommitment>, FrostError> {
+    let mut sorted = commitments.to_vec();
+    sorted.sort_unstable_by_key(|c| c.index);
+    if let Some(c) = sorted.iter().find(|c| c.index == 0) {
+        return Err(FrostError::InvalidParticipantIndex(c.index));
+    }
+    if let Some(w) = sorted.windows(2).find(|w| w[0].index == w[1].index) {
+        return Err(FrostError::DuplicateParticipant(w[0].index));
+    }
+    if sorted.len() < usize::from(threshold) {
+        return Err(FrostError::NotEnoughSigners);
+    }
+    Ok(sorted)
+}
+
+/// Calculate the binding factor, \\( \rho_i = H(i || Y || B || m) \\), of every signer and the group commitment,
+/// \\( R = \sum D_j + \rho_j.E_j \\), for a sorted signing set. Binding the group public key, _Y_, ties each
+/// signing session to a single group.
+fn group_commitment(
+    commitments: &[NonceCommitment],
+    group_public_key: &RistrettoPublicKey,
+    message: &[u8],
+) -> (Vec<RistrettoSecretKey>, RistrettoPublicKey) {
+    let mut encoded = Vec::with_capacity(8 + commitments.len() * 66);
+    encoded.extend_from_slice(&(commitments.len() as u64).to_le_bytes());
+    for c in commitments {
+        encoded.extend_from_slice(&c.index.to_le_bytes());
+        encoded.extend_from_slice(c.hiding.as_bytes());
+        encoded.extend_from_slice(c.binding.as_bytes());
+    }
+    let binding_factors: Vec<RistrettoSecretKey> = commitments
+        .iter()
+        .map(|c| {
+            let hash = Blake2b::new()
+                .chain(FROST_BINDING_LABEL)
+                .chain(c.index.to_le_bytes())
+                .chain(group_public_key.as_bytes())
+                .chain(&encoded)
+                .chain(message)
+                .finalize();
+            let mut bytes = [0u8; 64];
+            bytes.copy_from_slice(&hash);
+            RistrettoSecretKey::from_uniform_bytes(&bytes)
+        })
+        .collect();
+    let parts: Vec<RistrettoPublicKey> = commitments
+        .iter()
+        .zip(binding_factors.iter())
+        .map(|(c, rho)| &c.hiding + &(rho * &c.binding))
+        .collect();
+    (binding_factors, RistrettoPublicKey::from_sum(&parts))
+}
+
+fn binding_factor_of<'a>(
+    commitments: &[NonceCommitment],
+    binding_factors: &'a [RistrettoSecretKey],
+    index: u16,
+) -> &'a RistrettoSecretKey {
+    let position = commitments
+        .iter()
+        .position(|c| c.index == index)
+        .expect("The signer is in the signing set");
+    &binding_factors[position]
+}
+
+/// The Lagrange coefficient, \\( \lambda_i = \prod_{j \ne i} \frac{j}{j - i} \\), of participant `index` for
+/// interpolating at zero over the signing set
+fn lagrange_coefficient(commitments: &[NonceCommitment], index: u16) -> RistrettoSecretKey {
+    let x_i = RistrettoSecretKey::from(u64::from(index));
+    let (numerator, denominator) = commitments.iter().filter(|c| c.index != index).fold(
+        (RistrettoSecretKey::from(1u64), RistrettoSecretKey::from(1u64)),
+        |(num, den), c| {
+            let x_j = RistrettoSecretKey::from(u64::from(c.index));
+            let diff = &x_j - &x_i;
+            (&num * &x_j, &den * &diff)
+        },
+    );
+    // Indices in a signing set are distinct, so the denominator is never zero
+    RistrettoSecretKey(numerator.0 * denominator.0.invert())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ristretto::test_common::seeded_rng;
+
+    fn sign_with(shares: &[&KeyShare], message: &[u8]) -> Result<RistrettoSchnorr, FrostError> {
+        let mut rng = rand::thread_rng();
+        let (nonces, commitments): (Vec<_>, Vec<_>) = shares.iter().map(|s| generate_nonces(s, &mut rng)).unzip();
+        let sig_shares = shares
+            .iter()
+            .zip(nonces)
+            .map(|(s, n)| sign(s, n, &commitments, message))
+            .collect::<Result<Vec<_>, _>>()?;
+        aggregate(&commitments, &sig_shares, shares[0].group_public_key(), message)
+    }
+
+    #[test]
+    fn two_of_three_round_trip() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let group_public_key = RistrettoPublicKey::from_secret_key(&secret);
+        let shares = trusted_dealer_keygen(&secret, 2, 3, &mut rng).unwrap();
+        assert_eq!(shares.len(), 3);
+        assert!(shares.iter().all(|s| s.group_public_key() == &group_public_key));
+        let message = b"Withdraw 100 XTR from cold storage";
+        for pair in &[[0, 1], [0, 2], [1, 2]] {
+            let signers = [&shares[pair[0]], &shares[pair[1]]];
+            let sig = sign_with(&signers, message).unwrap();
+            assert!(sig.verify_message(&group_public_key, message));
+            assert!(!sig.verify_message(&group_public_key, b"Withdraw 1000 XTR from cold storage"));
+        }
+        // All three signers may take part too
+        let signers = [&shares[0], &shares[1], &shares[2]];
+        assert!(sign_with(&signers, message).is_ok());
+    }
+
+    #[test]
+    fn too_few_signers() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let shares = trusted_dealer_keygen(&secret, 2, 3, &mut rng).unwrap();
+        assert_eq!(sign_with(&[&shares[1]], b"msg"), Err(FrostError::NotEnoughSigners));
+        assert_eq!(
+            trusted_dealer_keygen(&secret, 4, 3, &mut rng).unwrap_err(),
+            FrostError::InvalidThreshold
+        );
+        assert_eq!(
+            trusted_dealer_keygen(&secret, 0, 3, &mut rng).unwrap_err(),
+            FrostError::InvalidThreshold
+        );
+    }
+
+    #[test]
+    fn lagrange_interpolation_recovers_secret() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let shares = trusted_dealer_keygen(&secret, 3, 5, &mut rng).unwrap();
+        let subset = [&shares[0], &shares[2], &shares[4]];
+        let commitments: Vec<NonceCommitment> = subset.iter().map(|s| generate_nonces(s, &mut rng).1).collect();
+        let recovered = subset.iter().fold(RistrettoSecretKey::default(), |acc, s| {
+            &acc + &(&lagrange_coefficient(&commitments, s.index()) * &s.secret_share)
+        });
+        assert_eq!(recovered, secret);
+    }
+
+    #[test]
+    fn invalid_share_is_detected() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let shares = trusted_dealer_keygen(&secret, 2, 3, &mut rng).unwrap();
+        let message = b"msg";
+        let (n1, c1) = generate_nonces(&shares[0], &mut rng);
+        let (n2, c2) = generate_nonces(&shares[1], &mut rng);
+        let commitments = vec![c1, c2];
+        let s1 = sign(&shares[0], n1, &commitments, message).unwrap();
+        let mut s2 = sign(&shares[1], n2, &commitments, message).unwrap();
+        let group_public_key = shares[0].group_public_key();
+        assert!(verify_signature_share(
+            &s1,
+            &shares[0].public_share(),
+            group_public_key,
+            &commitments,
+            message
+        ));
+        s2.z = &s2.z + &RistrettoSecretKey::from(1u64);
+        assert!(!verify_signature_share(
+            &s2,
+            &shares[1].public_share(),
+            group_public_key,
+            &commitments,
+            message
+        ));
+        assert_eq!(
+            aggregate(&commitments, &[s1, s2], group_public_key, message),
+            Err(FrostError::InvalidAggregateSignature)
+        );
+    }
+
+    #[test]
+    fn binding_factors_depend_on_group_key() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let shares = trusted_dealer_keygen(&secret, 2, 3, &mut rng).unwrap();
+        let commitments = signing_set(
+            &[
+                generate_nonces(&shares[0], &mut rng).1,
+                generate_nonces(&shares[1], &mut rng).1,
+            ],
+            2,
+        )
+        .unwrap();
+        let (_, other_key) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (rho, r) = group_commitment(&commitments, shares[0].group_public_key(), b"msg");
+        let (rho_other, r_other) = group_commitment(&commitments, &other_key, b"msg");
+        assert_ne!(rho, rho_other);
+        assert_ne!(r, r_other);
+    }
+
+    #[test]
+    fn mismatched_nonces_are_rejected() {
+        let mut rng = seeded_rng(0x5eed);
+        let secret = RistrettoSecretKey::random(&mut rng);
+        let shares = trusted_dealer_keygen(&secret, 2, 3, &mut rng).unwrap();
+        let (n1, _) = generate_nonces(&shares[0], &mut rng);
+        let (_, c1) = generate_nonces(&shares[0], &mut rng);
+        let (_, c2) = generate_nonces(&shares[1], &mut rng);
+        assert_eq!(
+            sign(&shares[0], n1, &[c1, c2.clone()], b"msg"),
+            Err(FrostError::NonceMismatch)
+        );
+        let (n1, _) = generate_nonces(&shares[0], &mut rng);
+        assert_eq!(
+            sign(&shares[0], n1, &[c2.clone(), c2], b"msg"),
+            Err(FrostError::DuplicateParticipant(2))
+        );
+    }
+}
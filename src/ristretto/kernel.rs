@@ -0,0 +1,149 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Transaction kernel excess signatures
+//!
+//! The kernel of a Mimblewimble transaction carries a signature made with the excess blinding factor, _x_, of the
+//! transaction. A valid signature proves that the excess, \\( X = x.G \\), is a commitment to zero, i.e. that the
+//! transaction balances, while binding the kernel's fee and lock height so that they cannot be altered afterwards.
+//!
+//! The challenge is \\( e = H(R || X || \text{fee} || \text{lock height}) \\), where _H_ is `Blake256` with a
+//! kernel-specific persona and the fee and lock height are encoded as 8-byte little-endian integers.
+
+use digest::Digest;
+use tari_utilities::ByteArray;
+
+use crate::{
+    hash::blake2::Blake256,
+    keys::PublicKey,
+    ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+};
+
+const KERNEL_CHALLENGE_PERSONA: &[u8] = b"tari.kernel_sig";
+
+/// Sign a transaction kernel with the excess blinding factor, `excess_blinding`, and the private `nonce`, when a
+/// single party knows the whole excess. The public excess and nonce are calculated from the secrets. Parties that each
+/// hold part of the excess should use [sign_kernel_partial] instead.
+///
+/// The nonce must be a fresh, uniformly random secret that is never reused, otherwise the excess blinding factor is
+/// revealed.
+pub fn sign_kernel(
+    excess_blinding: &RistrettoSecretKey,
+    nonce: &RistrettoSecretKey,
+    fee: u64,
+    lock_height: u64,
+) -> RistrettoSchnorr {
+    let public_excess = RistrettoPublicKey::from_secret_key(excess_blinding);
+    let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+    sign_kernel_partial(excess_blinding, nonce, &public_excess, &public_nonce, fee, lock_height)
+}
+
+/// Create one party's share of a kernel signature. `aggregate_excess` and `aggregate_nonce` are the sums of the public
+/// excesses and public nonces of all the parties, and the challenge is calculated over them. The share carries the
+/// party's own public nonce, \\( R_i \\), so the shares of all parties can be added together with `+` to give a
+/// signature that verifies against the aggregate excess with [verify_kernel_signature].
+///
+/// A single share does not verify on its own. The nonce must be a fresh, uniformly random secret that is never
+/// reused.
+pub fn sign_kernel_partial(
+    excess_blinding: &RistrettoSecretKey,
+    nonce: &RistrettoSecretKey,
+    aggregate_excess: &RistrettoPublicKey,
+    aggregate_nonce: &RistrettoPublicKey,
+    fee: u64,
+    lock_height: u64,
+) -> RistrettoSchnorr {
+    let e = kernel_challenge(aggregate_nonce, aggregate_excess, fee, lock_height);
+    RistrettoSchnorr::sign(excess_blinding.clone(), nonce.clone(), e.as_bytes())
+        .expect("A reduced challenge is always a valid scalar")
+}
+
+/// Returns true if `signature` is a valid kernel signature for `public_excess`, `fee` and `lock_height`, as created by
+/// [sign_kernel].
+pub fn verify_kernel_signature(
+    signature: &RistrettoSchnorr,
+    public_excess: &RistrettoPublicKey,
+    fee: u64,
+    lock_height: u64,
+) -> bool {
+    let e = kernel_challenge(signature.get_public_nonce(), public_excess, fee, lock_height);
+    signature.verify(public_excess, &e)
+}
+
+/// Calculates the kernel challenge, \\( e = H(R || X || \text{fee} || \text{lock height}) \\), reduced to a scalar
+fn kernel_challenge(
+    public_nonce: &RistrettoPublicKey,
+    public_excess: &RistrettoPublicKey,
+    fee: u64,
+    lock_height: u64,
+) -> RistrettoSecretKey {
+    let hash = Blake256::with_params(&[], &[], KERNEL_CHALLENGE_PERSONA)
+        .chain(public_nonce.as_bytes())
+        .chain(public_excess.as_bytes())
+        .chain(fee.to_le_bytes())
+        .chain(lock_height.to_le_bytes())
+        .finalize();
+    RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ristretto::test_common::get_keypair;
+
+    #[test]
+    fn kernel_signature_round_trip() {
+        let (x, excess) = get_keypair();
+        let (r, nonce) = get_keypair();
+        let sig = sign_kernel(&x, &r, 250, 1000);
+        assert_eq!(sig.get_public_nonce(), &nonce);
+        assert!(verify_kernel_signature(&sig, &excess, 250, 1000));
+        // Tampering with the fee or lock height invalidates the signature
+        assert!(!verify_kernel_signature(&sig, &excess, 251, 1000));
+        assert!(!verify_kernel_signature(&sig, &excess, 250, 999));
+        // The fee and lock height are not interchangeable
+        assert!(!verify_kernel_signature(&sig, &excess, 1000, 250));
+        // A signature for a different excess is rejected too
+        let (_, other) = get_keypair();
+        assert!(!verify_kernel_signature(&sig, &other, 250, 1000));
+    }
+
+    #[test]
+    fn aggregated_kernel_signature() {
+        // Two parties each contribute part of the excess and the nonce, sign with the aggregate public values and
+        // add their partial signatures together.
+        let (x1, excess1) = get_keypair();
+        let (x2, excess2) = get_keypair();
+        let (r1, nonce1) = get_keypair();
+        let (r2, nonce2) = get_keypair();
+        let excess = &excess1 + &excess2;
+        let nonce = &nonce1 + &nonce2;
+        let sig1 = sign_kernel_partial(&x1, &r1, &excess, &nonce, 50, 0);
+        let sig2 = sign_kernel_partial(&x2, &r2, &excess, &nonce, 50, 0);
+        assert_eq!(sig1.get_public_nonce(), &nonce1);
+        assert!(!verify_kernel_signature(&sig1, &excess, 50, 0));
+        let sig = &sig1 + &sig2;
+        assert_eq!(sig.get_public_nonce(), &nonce);
+        assert!(verify_kernel_signature(&sig, &excess, 50, 0));
+        assert!(!verify_kernel_signature(&sig, &excess, 51, 0));
+    }
+}
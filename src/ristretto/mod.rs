@@ -20,24 +20,38 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod blind_signature;
+pub mod challenge;
+pub mod commitment_proofs;
 pub mod constants;
 pub mod dalek_range_proof;
+pub mod designated_verifier;
+pub mod dleq;
+pub mod encrypted_value;
+pub mod frost;
+pub mod kernel;
+pub mod key_manager;
 #[cfg(feature = "musig")]
 pub mod musig;
+pub mod nums;
+pub mod output_mask;
 pub mod pedersen;
 pub mod ristretto_com_sig;
 pub mod ristretto_keys;
 pub mod ristretto_sig;
 pub mod serialize;
+pub mod set_membership;
+pub mod stealth;
 pub mod utils;
+pub mod x25519;
 
 // Re-export
 pub use self::{
     ristretto_com_sig::RistrettoComSig,
-    ristretto_keys::{RistrettoPublicKey, RistrettoSecretKey},
+    ristretto_keys::{CompressedRistrettoPublicKey, RistrettoPublicKey, RistrettoSecretKey},
     ristretto_sig::RistrettoSchnorr,
 };
 
 // test modules
 #[cfg(test)]
-mod test_common;
+pub(crate) mod test_common;
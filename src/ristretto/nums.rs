@@ -0,0 +1,83 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Derivation of Nothing Up My Sleeve (NUMS) points, i.e. points whose discrete log with respect to the Ristretto
+//! generator, or to each other, nobody knows.
+
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
+use sha2::{Digest, Sha512};
+
+use crate::ristretto::RistrettoPublicKey;
+
+/// The domain used to derive the built-in [RISTRETTO_NUMS_POINTS](crate::ristretto::constants::RISTRETTO_NUMS_POINTS).
+/// These points predate domain separation, so the default domain is empty.
+pub const DEFAULT_DOMAIN: &[u8] = b"";
+
+/// Derive the NUMS point at position `index` for the given `domain`.
+///
+/// The points are derived sequentially, starting from the compressed Ristretto generator, \\( P_{-1} = G \\):
+/// $$
+///   P_i = \mathrm{from\\_uniform\\_bytes}(\mathrm{SHA512}(domain || P_{i-1}))
+/// $$
+/// where the previous point is hashed in its 32-byte compressed form. `from_uniform_bytes` maps uniformly random bytes
+/// to a point without revealing its discrete log, so nobody knows the relationship between any of the points.
+///
+/// Using [DEFAULT_DOMAIN] reproduces the built-in constants. A protocol that needs its own independent generators
+/// should pass a distinct domain string. Deriving the point at `index` takes `index + 1` hashes.
+pub fn derive_nums_point(index: usize, domain: &[u8]) -> RistrettoPublicKey {
+    let mut point = RISTRETTO_BASEPOINT_POINT;
+    for _ in 0..=index {
+        let hash = Sha512::new()
+            .chain(domain)
+            .chain(point.compress().as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash);
+        point = RistrettoPoint::from_uniform_bytes(&bytes);
+    }
+    RistrettoPublicKey::new_from_pk(point)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ristretto::constants::RISTRETTO_NUMS_POINTS;
+
+    #[test]
+    fn default_domain_reproduces_built_in_points() {
+        for (i, p) in RISTRETTO_NUMS_POINTS.iter().enumerate() {
+            assert_eq!(
+                derive_nums_point(i, DEFAULT_DOMAIN),
+                RistrettoPublicKey::new_from_pk(*p)
+            );
+        }
+    }
+
+    #[test]
+    fn domains_are_separated() {
+        let a = derive_nums_point(0, b"tari.test.a");
+        let b = derive_nums_point(0, b"tari.test.b");
+        assert_ne!(a, b);
+        assert_ne!(a, derive_nums_point(0, DEFAULT_DOMAIN));
+        assert_ne!(a, derive_nums_point(1, b"tari.test.a"));
+    }
+}
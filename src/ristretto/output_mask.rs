@@ -0,0 +1,127 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A wallet-oriented bundle of a committed value and its blinding factor
+
+use zeroize::Zeroize;
+
+use crate::{
+    commitment::HomomorphicCommitmentFactory,
+    ristretto::{
+        pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+        RistrettoSecretKey,
+    },
+};
+
+/// The opening of a Pedersen commitment, i.e. a value and the blinding factor it is committed with. Keeping the two
+/// together avoids bugs where the value of one output is paired with the blinding factor of another.
+///
+/// Masks combine homomorphically, like the commitments they open: the commitment of a
+/// [combined](OutputMask::combine) mask is the sum of the commitments of its parts. Both the value and the blinding
+/// factor are zeroized when the mask is dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputMask {
+    value: u64,
+    blinding: RistrettoSecretKey,
+}
+
+impl OutputMask {
+    /// Create a new mask for `value` with the given blinding factor
+    pub fn new(value: u64, blinding: RistrettoSecretKey) -> Self {
+        OutputMask { value, blinding }
+    }
+
+    /// The committed value
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The blinding factor
+    pub fn blinding(&self) -> &RistrettoSecretKey {
+        &self.blinding
+    }
+
+    /// The commitment to this mask's value and blinding factor, \\( k.G + v.H \\), using the generators of `factory`
+    pub fn commitment(&self, factory: &PedersenCommitmentFactory) -> PedersenCommitment {
+        factory.commit_value(&self.blinding, self.value)
+    }
+
+    /// The mask that opens the sum of the commitments of `self` and `other`. Returns `None` if the sum of the values
+    /// overflows.
+    pub fn combine(&self, other: &OutputMask) -> Option<OutputMask> {
+        let value = self.value.checked_add(other.value)?;
+        Some(OutputMask::new(value, &self.blinding + &other.blinding))
+    }
+
+    /// Split `other` off this mask, e.g. a payment off an input, returning the mask for the remainder (the change).
+    /// The commitment of the result is the commitment of `self` minus the commitment of `other`. Returns `None` if
+    /// `other` holds a larger value than `self`.
+    pub fn split(&self, other: &OutputMask) -> Option<OutputMask> {
+        let value = self.value.checked_sub(other.value)?;
+        Some(OutputMask::new(value, &self.blinding - &other.blinding))
+    }
+}
+
+impl Drop for OutputMask {
+    fn drop(&mut self) {
+        // The blinding factor zeroizes itself when it is dropped
+        self.value.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::keys::SecretKey;
+
+    fn random_mask(value: u64) -> OutputMask {
+        OutputMask::new(value, RistrettoSecretKey::random(&mut rand::thread_rng()))
+    }
+
+    #[test]
+    fn masks_combine_homomorphically() {
+        let factory = PedersenCommitmentFactory::default();
+        let a = random_mask(100);
+        let b = random_mask(250);
+        let sum = a.combine(&b).unwrap();
+        assert_eq!(sum.value(), 350);
+        let c = sum.commitment(&factory);
+        assert_eq!(c, &a.commitment(&factory) + &b.commitment(&factory));
+        assert!(factory.open_value(sum.blinding(), 350, &c));
+        assert!(random_mask(u64::MAX).combine(&a).is_none());
+    }
+
+    #[test]
+    fn split_change() {
+        let factory = PedersenCommitmentFactory::default();
+        let input = random_mask(1000);
+        let payment = random_mask(600);
+        let change = input.split(&payment).unwrap();
+        assert_eq!(change.value(), 400);
+        assert_eq!(
+            change.commitment(&factory),
+            &input.commitment(&factory) - &payment.commitment(&factory)
+        );
+        assert_eq!(change.combine(&payment).unwrap(), input);
+        assert!(payment.split(&input).is_none());
+    }
+}
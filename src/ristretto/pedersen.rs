@@ -20,17 +20,19 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{borrow::Borrow, iter::Sum};
+use std::{borrow::Borrow, convert::TryFrom, iter::Sum};
 
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_POINT,
-    ristretto::RistrettoPoint,
+    ristretto::{RistrettoBasepointTable, RistrettoPoint},
     scalar::Scalar,
-    traits::MultiscalarMul,
+    traits::{Identity, MultiscalarMul},
 };
+use tari_utilities::{ByteArray, ByteArrayError};
 
 use crate::{
     commitment::{HomomorphicCommitment, HomomorphicCommitmentFactory},
+    errors::CommitmentError,
     ristretto::{constants::RISTRETTO_NUMS_POINTS, RistrettoPublicKey, RistrettoSecretKey},
 };
 
@@ -57,6 +59,208 @@ impl PedersenCommitmentFactory {
     pub fn new(G: RistrettoPoint, H: RistrettoPoint) -> PedersenCommitmentFactory {
         PedersenCommitmentFactory { G, H }
     }
+
+    /// Create a new Ristretto Commitment factory with custom generators, e.g. independently generated NUMS points for
+    /// a separate protocol domain. Returns an error if either generator is the identity, or if they are equal.
+    ///
+    /// The generators must be independent, i.e. nobody may know the discrete log of _H_ with respect to _G_, for the
+    /// commitments to be binding. Commitments made with different generators cannot be meaningfully combined with or
+    /// compared to commitments from the default factory.
+    #[allow(non_snake_case)]
+    pub fn try_new(G: RistrettoPoint, H: RistrettoPoint) -> Result<PedersenCommitmentFactory, CommitmentError> {
+        if G == RistrettoPoint::identity() || H == RistrettoPoint::identity() {
+            return Err(CommitmentError::IdentityGenerator);
+        }
+        if G == H {
+            return Err(CommitmentError::DuplicateGenerators);
+        }
+        Ok(PedersenCommitmentFactory::new(G, H))
+    }
+
+    /// Build a factory with precomputed multiplication tables for both generators. This makes repeated commitments
+    /// considerably faster, at the cost of a one-off setup and roughly 60kB of memory. The commitments produced are
+    /// identical to those produced by this factory.
+    pub fn with_precomputation(&self) -> PrecomputedPedersenCommitmentFactory {
+        PrecomputedPedersenCommitmentFactory {
+            G_table: RistrettoBasepointTable::create(&self.G),
+            H_table: RistrettoBasepointTable::create(&self.H),
+            base: self.clone(),
+        }
+    }
+
+    /// Commit to a vector of values, each against its own value generator, using the factory's blinding generator:
+    /// $$ C = k.G + \sum v_j.H_j $$
+    /// The factory's own value generator, _H_, is not used. Returns an error if `values` and `value_bases` have
+    /// different lengths. The value generators must be independent of each other and of _G_ for the commitment to be
+    /// binding.
+    pub fn commit_vector(
+        &self,
+        k: &RistrettoSecretKey,
+        values: &[RistrettoSecretKey],
+        value_bases: &[RistrettoPoint],
+    ) -> Result<PedersenCommitment, CommitmentError> {
+        if values.len() != value_bases.len() {
+            return Err(CommitmentError::VectorLengthMismatch {
+                values: values.len(),
+                generators: value_bases.len(),
+            });
+        }
+        let scalars = values.iter().map(|v| v.0).chain(std::iter::once(k.0));
+        let points = value_bases.iter().chain(std::iter::once(&self.G));
+        let c = RistrettoPoint::multiscalar_mul(scalars, points);
+        Ok(HomomorphicCommitment(RistrettoPublicKey::new_from_pk(c)))
+    }
+
+    /// Lazily commit to a stream of values, pairing each value with the blinding factor at the same position in
+    /// `blindings`. The returned iterator computes one commitment per call to `next`, so only the current blinding
+    /// factor and value are held in memory; nothing is buffered. Iteration stops as soon as either input is exhausted.
+    pub fn commit_iter<'a, B, K, V>(&'a self, blindings: B, values: V) -> impl Iterator<Item = PedersenCommitment> + 'a
+    where
+        B: IntoIterator<Item = K>,
+        B::IntoIter: 'a,
+        K: Borrow<RistrettoSecretKey>,
+        V: IntoIterator<Item = u64>,
+        V::IntoIter: 'a,
+    {
+        blindings
+            .into_iter()
+            .zip(values)
+            .map(move |(k, v)| self.commit_value(k.borrow(), v))
+    }
+
+    /// Commit to each of the 64 bits of `value` separately, least-significant bit first, using the blinding factor at
+    /// the same position in `blindings`: \\( C_i = k_i.G + b_i.H \\). The weighted sum of the bit commitments is a
+    /// commitment to `value`:
+    /// $$ \sum 2^i.C_i = (\sum 2^i k_i).G + v.H $$
+    /// This is a building block for custom proofs about the bits of a committed value; on its own, it proves nothing
+    /// about the bits actually being 0 or 1.
+    pub fn commit_bits(&self, value: u64, blindings: &[RistrettoSecretKey; 64]) -> Vec<PedersenCommitment> {
+        blindings
+            .iter()
+            .enumerate()
+            .map(|(i, k)| self.commit_value(k, (value >> i) & 1))
+            .collect()
+    }
+
+    /// Commit to a signed integer `value` with blinding factor `k`. Negative values are mapped to the scalar field as
+    /// \\( l - |v| \\), where _l_ is the group order, so a commitment to `-v` is the additive inverse of a commitment
+    /// to `v` with the same blinding factor, and commitments to balance deltas sum as expected.
+    ///
+    /// Range proofs cannot be made for negative values directly, since the committed scalar is then a very large
+    /// positive number.
+    pub fn commit_signed_value(&self, k: &RistrettoSecretKey, value: i64) -> PedersenCommitment {
+        let v = RistrettoSecretKey(signed_scalar(value));
+        self.commit(k, &v)
+    }
+
+    /// Commit to a fixed point amount with `decimals` decimal places, e.g. 1.23 with 2 decimals is given as an
+    /// `integer_amount` of 1 and a `fractional_amount` of 23. The amount is scaled to an integer number of the
+    /// smallest units, \\( v = a \cdot 10^d + f \\), which is committed to with blinding factor `k`, so the
+    /// commitment is identical to [commit_value](HomomorphicCommitmentFactory::commit_value) of 123 in this example.
+    ///
+    /// Returns `InvalidFractionalAmount` if `fractional_amount` has more than `decimals` digits, and
+    /// `FixedPointOverflow` if the scaled amount does not fit in a `u64`.
+    pub fn commit_fixed_point(
+        &self,
+        k: &RistrettoSecretKey,
+        integer_amount: u64,
+        fractional_amount: u64,
+        decimals: u8,
+    ) -> Result<PedersenCommitment, CommitmentError> {
+        let scale = 10u64
+            .checked_pow(u32::from(decimals))
+            .ok_or(CommitmentError::FixedPointOverflow)?;
+        if fractional_amount >= scale {
+            return Err(CommitmentError::InvalidFractionalAmount);
+        }
+        let value = integer_amount
+            .checked_mul(scale)
+            .and_then(|v| v.checked_add(fractional_amount))
+            .ok_or(CommitmentError::FixedPointOverflow)?;
+        Ok(self.commit_value(k, value))
+    }
+
+    /// Test whether the given blinding factor `k` and signed `value` open the given commitment, as created by
+    /// [commit_signed_value](PedersenCommitmentFactory::commit_signed_value).
+    pub fn open_signed_value(&self, k: &RistrettoSecretKey, value: i64, commitment: &PedersenCommitment) -> bool {
+        let v = RistrettoSecretKey(signed_scalar(value));
+        self.open(k, &v, commitment)
+    }
+
+    /// Commit to value `v` with blinding factor `k` against the tweaked value generator \\( H' = H + t.G \\), where
+    /// _t_ is a public `tweak`. That is, $$ C = k.G + v.H' = (k + vt).G + v.H $$
+    ///
+    /// Commitments made with the same tweak are homomorphic in the usual way, since \\( H' \\) is simply another
+    /// generator: the sum of two commitments opens with the sum of the blinding factors and values against the same
+    /// tweak. Commitments made with different tweaks cannot be combined meaningfully. A zero tweak produces the same
+    /// commitment as [commit](HomomorphicCommitmentFactory::commit).
+    pub fn commit_with_tweak(
+        &self,
+        k: &RistrettoSecretKey,
+        v: &RistrettoSecretKey,
+        tweak: &RistrettoSecretKey,
+    ) -> PedersenCommitment {
+        let c = RistrettoPoint::multiscalar_mul(&[v.0, k.0 + v.0 * tweak.0], &[self.H, self.G]);
+        HomomorphicCommitment(RistrettoPublicKey::new_from_pk(c))
+    }
+
+    /// Test whether the given blinding factor `k` and value `v` open a commitment that was made with
+    /// [commit_with_tweak](PedersenCommitmentFactory::commit_with_tweak) and the same `tweak`.
+    pub fn open_with_tweak(
+        &self,
+        k: &RistrettoSecretKey,
+        v: &RistrettoSecretKey,
+        tweak: &RistrettoSecretKey,
+        commitment: &PedersenCommitment,
+    ) -> bool {
+        let c_test = self.commit_with_tweak(k, v, tweak);
+        commitment.0 == c_test.0
+    }
+
+    /// Remove a publicly known value from a commitment, returning \\( C - v.H \\).
+    ///
+    /// If `commitment` really is a commitment to `value`, the result is \\( k.G \\), i.e. a public key for the
+    /// blinding factor. Knowing `value` alone is not enough to confirm that the commitment opens to it; the holder of
+    /// the blinding factor proves this by producing a signature (or other proof of knowledge of the discrete log)
+    /// that is valid for the returned key. If the commitment is to a different value, nobody can produce such a
+    /// proof.
+    pub fn commitment_minus_value(&self, commitment: &PedersenCommitment, value: u64) -> RistrettoPublicKey {
+        let p = RistrettoPoint::from(commitment.as_public_key()) - Scalar::from(value) * self.H;
+        RistrettoPublicKey::new_from_pk(p)
+    }
+
+    /// Recover the value difference \\( \delta = v_1 - v_2 \\) from a point \\( \delta.H \\), such as one returned by
+    /// [value_difference_point](PedersenCommitment::value_difference_point) for two commitments with the same blinding
+    /// factor. Returns `None` if \\( |\delta| \\) is larger than `max`, or if the point is not a multiple of _H_ at all
+    /// (e.g. because the blinding factors were different).
+    ///
+    /// The difference is found by brute force, comparing the point against \\( \pm\delta.H \\) for every
+    /// \\( \delta \\) from zero to `max`, so the cost is O(max) point additions. Only use this for small bounds.
+    pub fn recover_value_difference(&self, diff_point: &RistrettoPublicKey, max: u64) -> Option<i64> {
+        let target = RistrettoPoint::from(diff_point);
+        let max = i64::try_from(max).unwrap_or(i64::MAX);
+        let mut candidate = RistrettoPoint::identity();
+        for delta in 0..=max {
+            if candidate == target {
+                return Some(delta);
+            }
+            if -candidate == target {
+                return Some(-delta);
+            }
+            candidate += self.H;
+        }
+        None
+    }
+}
+
+/// Maps a signed integer to the scalar field, with negative values becoming \\( l - |v| \\)
+fn signed_scalar(value: i64) -> Scalar {
+    let v = Scalar::from(value.unsigned_abs());
+    if value < 0 {
+        -v
+    } else {
+        v
+    }
 }
 
 impl Default for PedersenCommitmentFactory {
@@ -96,6 +300,145 @@ impl HomomorphicCommitmentFactory for PedersenCommitmentFactory {
     }
 }
 
+/// A Pedersen commitment factory that uses precomputed multiplication tables for its generators. Create one with
+/// [PedersenCommitmentFactory::with_precomputation] when many commitments will be made against the same generators.
+#[allow(non_snake_case)]
+pub struct PrecomputedPedersenCommitmentFactory {
+    base: PedersenCommitmentFactory,
+    G_table: RistrettoBasepointTable,
+    H_table: RistrettoBasepointTable,
+}
+
+impl PrecomputedPedersenCommitmentFactory {
+    /// The factory that this instance was precomputed from
+    pub fn base(&self) -> &PedersenCommitmentFactory {
+        &self.base
+    }
+}
+
+impl HomomorphicCommitmentFactory for PrecomputedPedersenCommitmentFactory {
+    type P = RistrettoPublicKey;
+
+    fn commit(&self, k: &RistrettoSecretKey, v: &RistrettoSecretKey) -> PedersenCommitment {
+        let c = &self.H_table * &v.0 + &self.G_table * &k.0;
+        HomomorphicCommitment(RistrettoPublicKey::new_from_pk(c))
+    }
+
+    fn zero(&self) -> PedersenCommitment {
+        self.base.zero()
+    }
+
+    fn open(&self, k: &RistrettoSecretKey, v: &RistrettoSecretKey, commitment: &PedersenCommitment) -> bool {
+        let c_test = self.commit(k, v);
+        commitment.0 == c_test.0
+    }
+
+    fn commit_value(&self, k: &RistrettoSecretKey, value: u64) -> PedersenCommitment {
+        let v = RistrettoSecretKey::from(value);
+        self.commit(k, &v)
+    }
+
+    fn open_value(&self, k: &RistrettoSecretKey, v: u64, commitment: &HomomorphicCommitment<Self::P>) -> bool {
+        let kv = RistrettoSecretKey::from(v);
+        self.open(k, &kv, commitment)
+    }
+}
+
+impl PedersenCommitment {
+    /// The commitment as a raw Ristretto point, for custom curve arithmetic
+    pub fn as_point(&self) -> RistrettoPoint {
+        RistrettoPoint::from(self.as_public_key())
+    }
+
+    /// Calculate \\( C_1 - C_2 \\). If both commitments were made with the same blinding factor, the difference is
+    /// \\( (v_1 - v_2).H \\), which can be recovered for small differences with
+    /// [recover_value_difference](PedersenCommitmentFactory::recover_value_difference).
+    pub fn value_difference_point(c1: &PedersenCommitment, c2: &PedersenCommitment) -> RistrettoPublicKey {
+        RistrettoPublicKey::new_from_pk(c1.as_point() - c2.as_point())
+    }
+
+    /// Re-randomize this commitment by adding a commitment to zero, \\( C' = C + (\sum r_i).G \\), using the blinding
+    /// generator of `factory`. The committed value is unchanged, but the new commitment cannot be linked to the old one
+    /// by anyone who does not know the extra blinding factors. The factory has a single blinding generator, so the
+    /// factors in `extra_blinding` are simply summed; an empty slice leaves the commitment unchanged.
+    ///
+    /// The owner must keep track of the new total blinding factor, \\( k + \sum r_i \\), since the re-blinded
+    /// commitment no longer opens with the original one.
+    pub fn reblind(
+        &self,
+        factory: &PedersenCommitmentFactory,
+        extra_blinding: &[RistrettoSecretKey],
+    ) -> PedersenCommitment {
+        let r = extra_blinding.iter().fold(Scalar::zero(), |acc, k| acc + k.0);
+        let c = RistrettoPoint::from(self.as_public_key()) + r * factory.G;
+        HomomorphicCommitment(RistrettoPublicKey::new_from_pk(c))
+    }
+}
+
+/// Wrap an arbitrary point as a commitment. Nothing about the point is checked: unless it was calculated as
+/// \\( k.G + v.H \\) by someone who kept _k_ and _v_, nobody can open the resulting commitment.
+impl From<RistrettoPoint> for PedersenCommitment {
+    fn from(p: RistrettoPoint) -> Self {
+        HomomorphicCommitment(RistrettoPublicKey::new_from_pk(p))
+    }
+}
+
+/// A Pedersen commitment held only as its 32-byte compressed encoding. A [PedersenCommitment] holds the decompressed
+/// point as well, which makes arithmetic fast but takes several times the memory, so this is the better choice for
+/// storing large numbers of commitments, e.g. a UTXO set.
+///
+/// The bytes are not validated on construction. They are decompressed, and checked, each time the commitment is
+/// converted into a [PedersenCommitment] for arithmetic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CompressedCommitment([u8; 32]);
+
+impl CompressedCommitment {
+    /// Wrap the given compressed commitment encoding
+    pub fn new(bytes: [u8; 32]) -> Self {
+        CompressedCommitment(bytes)
+    }
+
+    /// The compressed encoding of the commitment
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Decompress the commitment, returning an error if the bytes are not a valid Ristretto point
+    pub fn decompress(&self) -> Result<PedersenCommitment, ByteArrayError> {
+        PedersenCommitment::from_bytes(&self.0)
+    }
+}
+
+impl From<&PedersenCommitment> for CompressedCommitment {
+    fn from(commitment: &PedersenCommitment) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(commitment.as_bytes());
+        CompressedCommitment(bytes)
+    }
+}
+
+impl From<PedersenCommitment> for CompressedCommitment {
+    fn from(commitment: PedersenCommitment) -> Self {
+        Self::from(&commitment)
+    }
+}
+
+impl TryFrom<&CompressedCommitment> for PedersenCommitment {
+    type Error = ByteArrayError;
+
+    fn try_from(commitment: &CompressedCommitment) -> Result<Self, Self::Error> {
+        commitment.decompress()
+    }
+}
+
+impl TryFrom<CompressedCommitment> for PedersenCommitment {
+    type Error = ByteArrayError;
+
+    fn try_from(commitment: CompressedCommitment) -> Result<Self, Self::Error> {
+        commitment.decompress()
+    }
+}
+
 impl<T> Sum<T> for PedersenCommitment
 where T: Borrow<PedersenCommitment>
 {
@@ -115,14 +458,23 @@ where T: Borrow<PedersenCommitment>
 mod test {
     use std::{
         collections::hash_map::DefaultHasher,
-        convert::From,
+        convert::{From, TryFrom, TryInto},
         hash::{Hash, Hasher},
     };
 
-    use tari_utilities::{message_format::MessageFormat, ByteArray};
+    use blake2::Blake2b;
+    use digest::Digest;
+    use tari_utilities::{
+        hex::{to_hex, Hex},
+        message_format::MessageFormat,
+        ByteArray,
+    };
 
     use super::*;
-    use crate::keys::{PublicKey, SecretKey};
+    use crate::{
+        keys::{PublicKey, SecretKey},
+        ristretto::{test_common::seeded_rng, RistrettoSchnorr},
+    };
 
     #[test]
     fn check_default_base() {
@@ -141,6 +493,18 @@ mod test {
         assert_eq!(c, c2);
     }
 
+    #[test]
+    fn try_from_slice() {
+        let mut rng = rand::thread_rng();
+        let (_, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let c = PedersenCommitment::from_public_key(&p);
+        let c2 = PedersenCommitment::try_from(c.as_ref()).unwrap();
+        assert_eq!(c, c2);
+        assert!(PedersenCommitment::try_from(&c.as_bytes()[..31]).is_err());
+        let bad = [0xffu8; 32];
+        assert!(PedersenCommitment::try_from(&bad[..]).is_err());
+    }
+
     #[test]
     fn commitment_sub() {
         let mut rng = rand::thread_rng();
@@ -165,7 +529,7 @@ mod test {
     fn check_open() {
         let factory = PedersenCommitmentFactory::default();
         let H = *RISTRETTO_PEDERSEN_H;
-        let mut rng = rand::thread_rng();
+        let mut rng = seeded_rng(0x5eed);
         for _ in 0..100 {
             let v = RistrettoSecretKey::random(&mut rng);
             let k = RistrettoSecretKey::random(&mut rng);
@@ -253,6 +617,28 @@ mod test {
         assert_eq!(c_sum, commitments.iter().sum());
     }
 
+    #[test]
+    fn accumulate_in_place() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let commitments = (0..10u64)
+            .map(|v| factory.commit_value(&RistrettoSecretKey::random(&mut rng), v))
+            .collect::<Vec<_>>();
+        let folded = commitments
+            .iter()
+            .skip(1)
+            .fold(commitments[0].clone(), |acc, c| &acc + c);
+        let mut c_sum = PedersenCommitment::default();
+        for c in &commitments {
+            c_sum += c;
+        }
+        assert_eq!(c_sum, folded);
+        for c in &commitments[1..] {
+            c_sum -= c;
+        }
+        assert_eq!(c_sum, commitments[0]);
+    }
+
     #[test]
     fn serialize_deserialize() {
         let mut rng = rand::thread_rng();
@@ -297,9 +683,468 @@ mod test {
         assert!(matches!(c2.cmp(&c3), std::cmp::Ordering::Greater));
     }
 
+    #[test]
+    fn precomputed_factory_matches_factory() {
+        let mut rng = rand::thread_rng();
+        let (_, h) = RistrettoPublicKey::random_keypair(&mut rng);
+        for factory in &[
+            PedersenCommitmentFactory::default(),
+            PedersenCommitmentFactory::new(RISTRETTO_PEDERSEN_G, RistrettoPoint::from(&h)),
+        ] {
+            let precomputed = factory.with_precomputation();
+            assert_eq!(precomputed.base(), factory);
+            assert_eq!(precomputed.zero(), factory.zero());
+            for _ in 0..10 {
+                let k = RistrettoSecretKey::random(&mut rng);
+                let v = RistrettoSecretKey::random(&mut rng);
+                let c = precomputed.commit(&k, &v);
+                assert_eq!(c.as_bytes(), factory.commit(&k, &v).as_bytes());
+                assert!(precomputed.open(&k, &v, &c));
+                assert!(factory.open(&k, &v, &c));
+                assert_eq!(precomputed.commit_value(&k, 42), factory.commit_value(&k, 42));
+                assert!(precomputed.open_value(&k, 42, &factory.commit_value(&k, 42)));
+            }
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn commit_with_tweak() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let v = RistrettoSecretKey::from(1234);
+        // A zero tweak is a normal commitment
+        let zero = RistrettoSecretKey::default();
+        let c = factory.commit_with_tweak(&k, &v, &zero);
+        assert_eq!(c, factory.commit(&k, &v));
+        assert!(factory.open_with_tweak(&k, &v, &zero, &c));
+        // A non-zero tweak moves the commitment by v.t.G
+        let t = RistrettoSecretKey::random(&mut rng);
+        let c_t = factory.commit_with_tweak(&k, &v, &t);
+        assert_ne!(c_t, c);
+        assert_eq!(c_t, factory.commit(&(&k + &(&v * &t)), &v));
+        let H_tweaked = RistrettoPoint::from(&RistrettoPublicKey::from_secret_key(&t)) + factory.H;
+        let tweaked_factory = PedersenCommitmentFactory::new(factory.G, H_tweaked);
+        assert_eq!(c_t, tweaked_factory.commit(&k, &v));
+        assert!(factory.open_with_tweak(&k, &v, &t, &c_t));
+        assert!(!factory.open_with_tweak(&k, &v, &zero, &c_t));
+        assert!(!factory.open(&k, &v, &c_t));
+        // Commitments with the same tweak are homomorphic
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let v2 = RistrettoSecretKey::from(766);
+        let c2_t = factory.commit_with_tweak(&k2, &v2, &t);
+        assert!(factory.open_with_tweak(&(&k + &k2), &RistrettoSecretKey::from(2000), &t, &(&c_t + &c2_t)));
+    }
+
+    #[test]
+    fn commit_signed_value() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let c_neg = factory.commit_signed_value(&k1, -5);
+        assert!(factory.open_signed_value(&k1, -5, &c_neg));
+        assert!(!factory.open_signed_value(&k1, 5, &c_neg));
+        let c_pos = factory.commit_value(&k2, 5);
+        let sum = &c_neg + &c_pos;
+        assert_eq!(sum, factory.commit_value(&(&k1 + &k2), 0));
+        // Non-negative values match commit_value
+        assert_eq!(factory.commit_signed_value(&k2, 5), c_pos);
+        assert!(factory.open_signed_value(&k2, 5, &c_pos));
+        // l - |v| mapping holds at the extremes too
+        let c_min = factory.commit_signed_value(&k1, i64::MIN);
+        let c_max = factory.commit_signed_value(&k2, i64::MAX);
+        assert_eq!(&c_min + &c_max, factory.commit_signed_value(&(&k1 + &k2), -1));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn custom_generators() {
+        let hash_to_point = |label: &[u8]| {
+            let mut bytes = [0u8; 64];
+            bytes.copy_from_slice(&Blake2b::digest(label));
+            RistrettoPoint::from_uniform_bytes(&bytes)
+        };
+        let G = hash_to_point(b"my_protocol.G");
+        let H = hash_to_point(b"my_protocol.H");
+        let factory = PedersenCommitmentFactory::try_new(G, H).unwrap();
+        let mut rng = rand::thread_rng();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c = factory.commit_value(&k, 5);
+        assert!(factory.open_value(&k, 5, &c));
+        assert!(!factory.open_value(&k, 6, &c));
+        assert_eq!(
+            RistrettoPoint::from(c.as_public_key()),
+            Scalar::from(5u64) * H + k.0 * G
+        );
+        assert_ne!(c, PedersenCommitmentFactory::default().commit_value(&k, 5));
+        // Invalid generators
+        let identity = RistrettoPoint::identity();
+        assert_eq!(
+            PedersenCommitmentFactory::try_new(identity, H),
+            Err(CommitmentError::IdentityGenerator)
+        );
+        assert_eq!(
+            PedersenCommitmentFactory::try_new(G, identity),
+            Err(CommitmentError::IdentityGenerator)
+        );
+        assert_eq!(
+            PedersenCommitmentFactory::try_new(G, G),
+            Err(CommitmentError::DuplicateGenerators)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn commit_vector() {
+        let mut rng = rand::thread_rng();
+        let H1 = *RISTRETTO_PEDERSEN_H;
+        let H2 = RISTRETTO_NUMS_POINTS[1];
+        let factory1 = PedersenCommitmentFactory::new(RISTRETTO_PEDERSEN_G, H1);
+        let factory2 = PedersenCommitmentFactory::new(RISTRETTO_PEDERSEN_G, H2);
+        let k1 = RistrettoSecretKey::random(&mut rng);
+        let k2 = RistrettoSecretKey::random(&mut rng);
+        let v1 = RistrettoSecretKey::from(100);
+        let v2 = RistrettoSecretKey::from(200);
+        let c = factory1
+            .commit_vector(&(&k1 + &k2), &[v1.clone(), v2.clone()], &[H1, H2])
+            .unwrap();
+        assert_eq!(c, &factory1.commit(&k1, &v1) + &factory2.commit(&k2, &v2));
+        // A single-value vector is a normal commitment
+        let c = factory1.commit_vector(&k1, &[v1.clone()], &[H1]).unwrap();
+        assert_eq!(c, factory1.commit(&k1, &v1));
+        // An empty vector commits to the blinding factor alone
+        let c = factory1.commit_vector(&k1, &[], &[]).unwrap();
+        assert_eq!(c.as_public_key(), &RistrettoPublicKey::from_secret_key(&k1));
+        assert_eq!(
+            factory1.commit_vector(&k1, &[v1, v2], &[H1]),
+            Err(CommitmentError::VectorLengthMismatch {
+                values: 2,
+                generators: 1
+            })
+        );
+    }
+
+    #[test]
+    fn commitment_minus_value() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let c = factory.commit_value(&k, 250);
+        assert_eq!(factory.commitment_minus_value(&c, 250), p);
+        assert_ne!(factory.commitment_minus_value(&c, 249), p);
+        // The blinding factor owner can prove knowledge of the discrete log of C - v.H
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"fee");
+        assert!(sig.verify_message(&factory.commitment_minus_value(&c, 250), b"fee"));
+        assert!(!sig.verify_message(&factory.commitment_minus_value(&c, 251), b"fee"));
+    }
+
+    #[test]
+    fn factories_can_be_trait_objects() {
+        let mut rng = rand::thread_rng();
+        let (_, h) = RistrettoPublicKey::random_keypair(&mut rng);
+        let factories: Vec<Box<dyn HomomorphicCommitmentFactory<P = RistrettoPublicKey>>> = vec![
+            Box::new(PedersenCommitmentFactory::default()),
+            Box::new(PedersenCommitmentFactory::new(
+                RISTRETTO_PEDERSEN_G,
+                RistrettoPoint::from(&h),
+            )),
+        ];
+        let k = RistrettoSecretKey::random(&mut rng);
+        let commitments: Vec<PedersenCommitment> = factories.iter().map(|f| f.commit_value(&k, 100)).collect();
+        for (factory, c) in factories.iter().zip(commitments.iter()) {
+            assert!(factory.open_value(&k, 100, c));
+            assert!(!factory.open_value(&k, 101, c));
+        }
+        // Different generators produce different commitments to the same opening
+        assert_ne!(commitments[0], commitments[1]);
+    }
+
     #[test]
     fn default_value() {
         let c = PedersenCommitment::default();
         assert_eq!(c, PedersenCommitment::from_public_key(&RistrettoPublicKey::default()));
     }
+
+    #[test]
+    fn reblind() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let v = RistrettoSecretKey::from(1234);
+        let c = factory.commit(&k, &v);
+        let r1 = RistrettoSecretKey::random(&mut rng);
+        let r2 = RistrettoSecretKey::random(&mut rng);
+        let c2 = c.reblind(&factory, &[r1.clone(), r2.clone()]);
+        assert_ne!(c, c2);
+        assert!(factory.open(&(&(&k + &r1) + &r2), &v, &c2));
+        assert!(!factory.open(&k, &v, &c2));
+        assert_eq!(c.reblind(&factory, &[]), c);
+    }
+
+    #[test]
+    fn recover_value_difference() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c1 = factory.commit_value(&k, 1042);
+        let c2 = factory.commit_value(&k, 1000);
+        let diff = PedersenCommitment::value_difference_point(&c1, &c2);
+        assert_eq!(factory.recover_value_difference(&diff, 100), Some(42));
+        let diff = PedersenCommitment::value_difference_point(&c2, &c1);
+        assert_eq!(factory.recover_value_difference(&diff, 100), Some(-42));
+        assert_eq!(factory.recover_value_difference(&diff, 41), None);
+        // Equal commitments have a zero difference
+        let diff = PedersenCommitment::value_difference_point(&c1, &c1);
+        assert_eq!(factory.recover_value_difference(&diff, 0), Some(0));
+        // Different blinding factors hide the difference
+        let c3 = factory.commit_value(&RistrettoSecretKey::random(&mut rng), 1000);
+        let diff = PedersenCommitment::value_difference_point(&c1, &c3);
+        assert_eq!(factory.recover_value_difference(&diff, 100), None);
+    }
+
+    #[test]
+    fn negated_commitment() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c = factory.commit_value(&k, 1234);
+        let neg_c = -&c;
+        assert_eq!(&c + &neg_c, PedersenCommitment::default());
+        assert!(factory.open_signed_value(&(-&k), -1234, &neg_c));
+        assert!(!factory.open_signed_value(&k, -1234, &neg_c));
+        assert_eq!(-neg_c, c);
+    }
+
+    #[test]
+    fn compressed_commitment() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        assert_eq!(std::mem::size_of::<CompressedCommitment>(), 32);
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c = factory.commit_value(&k, 1234);
+        let compressed = CompressedCommitment::from(&c);
+        assert_eq!(&compressed.as_bytes()[..], c.as_bytes());
+        let decompressed: PedersenCommitment = compressed.try_into().unwrap();
+        assert_eq!(decompressed, c);
+        assert!(factory.open_value(&k, 1234, &decompressed));
+        // Arithmetic works on the decompressed commitments
+        let c2 = CompressedCommitment::from(factory.commit_value(&k, 1));
+        let sum = &compressed.decompress().unwrap() + &c2.decompress().unwrap();
+        assert!(factory.open_value(&(&k + &k), 1235, &sum));
+        // Invalid encodings are only detected on decompression
+        let invalid = CompressedCommitment::new([0xff; 32]);
+        assert!(PedersenCommitment::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn commit_fixed_point() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c = factory.commit_fixed_point(&k, 1, 23, 2).unwrap();
+        assert_eq!(c, factory.commit_value(&k, 123));
+        // Leading zeros in the fraction are implied by the number of decimals
+        let c = factory.commit_fixed_point(&k, 1, 5, 2).unwrap();
+        assert!(factory.open_value(&k, 105, &c));
+        assert_eq!(
+            factory.commit_fixed_point(&k, 1, 0, 0).unwrap(),
+            factory.commit_value(&k, 1)
+        );
+        assert_eq!(
+            factory.commit_fixed_point(&k, 1, 123, 2),
+            Err(CommitmentError::InvalidFractionalAmount)
+        );
+        // The largest amount that fits, and the next one up
+        assert!(factory.commit_fixed_point(&k, u64::MAX / 100, 15, 2).is_ok());
+        assert_eq!(
+            factory.commit_fixed_point(&k, u64::MAX / 100, 16, 2),
+            Err(CommitmentError::FixedPointOverflow)
+        );
+        assert_eq!(
+            factory.commit_fixed_point(&k, u64::MAX / 10, 0, 2),
+            Err(CommitmentError::FixedPointOverflow)
+        );
+        assert_eq!(
+            factory.commit_fixed_point(&k, 0, 0, 20),
+            Err(CommitmentError::FixedPointOverflow)
+        );
+    }
+
+    #[test]
+    fn default_is_identity() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        assert_eq!(PedersenCommitment::default(), factory.zero());
+        let commitments = (0..5u64)
+            .map(|v| factory.commit_value(&RistrettoSecretKey::random(&mut rng), v))
+            .collect::<Vec<_>>();
+        let sum: PedersenCommitment = commitments.iter().sum();
+        let folded = commitments
+            .into_iter()
+            .fold(PedersenCommitment::default(), |a, b| a + b);
+        assert_eq!(folded, sum);
+    }
+
+    #[test]
+    fn point_round_trip() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let c = factory.commit_value(&k, 99);
+        let p = c.as_point();
+        assert_eq!(p, k.0 * factory.G + Scalar::from(99u64) * factory.H);
+        let c2 = PedersenCommitment::from(p);
+        assert_eq!(c2, c);
+        assert!(factory.open_value(&k, 99, &c2));
+    }
+
+    /// Fixed `(k, v, C = k.G + v.H)` vectors for the default factory, calculated with an independent implementation of
+    /// ristretto255. A failure here means the generator constants or the commitment arithmetic have changed, which
+    /// breaks every existing commitment and must be intentional.
+    #[test]
+    fn commitment_test_vectors() {
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                0,
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            ),
+            (
+                "0100000000000000000000000000000000000000000000000000000000000000",
+                1,
+                "26e96db06130c965522c212b7c08f901a3710d1d61146a839fac0b14b7dcfb0d",
+            ),
+            (
+                "0200000000000000000000000000000000000000000000000000000000000000",
+                0,
+                "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+            ),
+            (
+                "d20a1feb8ca954ab000000000000000000000000000000000000000000000000",
+                1_000_000,
+                "5079f4f770ecfef337c67187061bc9c122d9a48f387b3a5ca77cd748c154cc49",
+            ),
+            (
+                "46e7138a55be6f633a250d060df14b9c7e5b318d08322421ff1651178893ed0e",
+                42,
+                "f0b2d67edd3256d024185156cc7ecf83e33ab3d475e15c864dcea5778aa1e84a",
+            ),
+            (
+                "f18f5e7f0763c884a75f15a0687a3f6ce2a7ff3fcf306e82f6cc57c126666c03",
+                u64::MAX,
+                "8ad606f0c5102ebbd1c403e75b3c31075ee97eb5627e9b8e1cb18c34c2893d62",
+            ),
+            (
+                "10a3ca8d0dd23c5d26f761497173b985a947fabeb555becd3b58c69bad853802",
+                123_456_789,
+                "7872a410c7f5b8fd4d20bd000199f35110e1652cd62d7e37f5005cf4ab635b69",
+            ),
+            (
+                "d09b7c567cb9a25927981f4f1ecb3a1fc1ec9727e27f043aedb1031b79163302",
+                0,
+                "aa59630341ed294d4e30a0a25656ec6df4ee0be3b5a423aef2defeb647586044",
+            ),
+            (
+                "ecd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010",
+                1,
+                "021743d2b4459ae456d7f96a7de1f46a0b7ddc461ffd768909bb2561df199d7a",
+            ),
+        ];
+        let factory = PedersenCommitmentFactory::default();
+        for (k, v, expected) in &vectors {
+            let k = RistrettoSecretKey::from_hex(k).unwrap();
+            let c = factory.commit_value(&k, *v);
+            assert_eq!(&c.to_hex(), expected);
+            assert!(factory.open_value(&k, *v, &PedersenCommitment::from_hex(expected).unwrap()));
+        }
+    }
+
+    #[test]
+    fn commitment_hex_formatting() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        for v in 0..10 {
+            let c = factory.commit_value(&RistrettoSecretKey::random(&mut rng), v);
+            let display = format!("{}", c);
+            assert_eq!(display.len(), 64);
+            assert!(display.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+            assert_eq!(format!("{:x}", c), display);
+            assert_eq!(format!("{:#x}", c), format!("0x{}", display));
+            assert_eq!(format!("{:?}", c), format!("HomomorphicCommitment({})", display));
+        }
+    }
+
+    #[test]
+    fn commit_iter() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let blindings: Vec<RistrettoSecretKey> = (0..20).map(|_| RistrettoSecretKey::random(&mut rng)).collect();
+        let values: Vec<u64> = (0..20u64).map(|v| v * 1_000).collect();
+        let eager: Vec<PedersenCommitment> = blindings
+            .iter()
+            .zip(values.iter())
+            .map(|(k, v)| factory.commit_value(k, *v))
+            .collect();
+        // Borrowed and owned blinding factors give the same result
+        let lazy: Vec<PedersenCommitment> = factory.commit_iter(&blindings, values.iter().copied()).collect();
+        assert_eq!(lazy, eager);
+        let lazy: Vec<PedersenCommitment> = factory.commit_iter(blindings.clone(), values.clone()).collect();
+        assert_eq!(lazy, eager);
+        // Nothing is computed until the iterator is driven, and a shorter input ends the stream
+        let mut iter = factory.commit_iter(&blindings, (0..5u64).map(|v| v * 1_000));
+        assert_eq!(iter.next(), Some(eager[0].clone()));
+        assert_eq!(iter.count(), 4);
+    }
+
+    #[test]
+    fn canonical_commitments() {
+        let factory = PedersenCommitmentFactory::default();
+        let c = factory.commit_value(&RistrettoSecretKey::random(&mut rand::thread_rng()), 100);
+        assert!(c.is_canonical());
+        assert!(PedersenCommitment::default().is_canonical());
+        let decoded = PedersenCommitment::from_bytes(c.as_bytes()).unwrap();
+        assert!(decoded.is_canonical());
+        assert_eq!(decoded, c);
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(serde_json::from_str::<PedersenCommitment>(&json).unwrap(), c);
+
+        let mut field_modulus = [0xffu8; 32];
+        field_modulus[0] = 0xed;
+        field_modulus[31] = 0x7f;
+        let mut negative = [0u8; 32];
+        negative[0] = 1;
+        let mut high_bit = [0u8; 32];
+        high_bit[31] = 0x80;
+        for bytes in &[field_modulus, negative, high_bit] {
+            assert!(PedersenCommitment::from_bytes(bytes).is_err());
+            assert!(PedersenCommitment::try_from(&bytes[..]).is_err());
+            let json = serde_json::to_string(&to_hex(&bytes[..])).unwrap();
+            assert!(serde_json::from_str::<PedersenCommitment>(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn commit_bits() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let blindings: [RistrettoSecretKey; 64] = [(); 64].map(|_| RistrettoSecretKey::random(&mut rng));
+        for value in &[0u64, 1, 0b1011_0110, 0xdead_beef_cafe_f00d, u64::MAX] {
+            let bits = factory.commit_bits(*value, &blindings);
+            assert_eq!(bits.len(), 64);
+            let powers: Vec<RistrettoSecretKey> = (0..64).map(|i| RistrettoSecretKey::from(1u64 << i)).collect();
+            let sum: PedersenCommitment = bits.iter().zip(powers.iter()).map(|(c, p)| c * p).sum();
+            let k = blindings
+                .iter()
+                .zip(powers.iter())
+                .fold(RistrettoSecretKey::default(), |acc, (k, p)| &acc + &(k * p));
+            assert_eq!(sum, factory.commit_value(&k, *value));
+            // Each bit commitment opens to the corresponding bit
+            for (i, (c, k)) in bits.iter().zip(blindings.iter()).enumerate() {
+                assert!(factory.open_value(k, (value >> i) & 1, c));
+            }
+        }
+    }
 }
@@ -20,11 +20,20 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use blake2::Blake2b;
+use digest::Digest;
+use tari_utilities::ByteArray;
+
 use crate::{
+    commitment::{HomomorphicCommitment, HomomorphicCommitmentFactory},
+    hash::blake2::Blake256,
     ristretto::{RistrettoPublicKey, RistrettoSecretKey},
     signatures::CommitmentSignature,
 };
 
+const OWNERSHIP_CHALLENGE_PERSONA: &[u8] = b"tari.com_ownership";
+const OWNERSHIP_NONCE_LABEL: &[u8] = b"tari.com_ownership.nonce";
+
 /// # A Commitment signature implementation on Ristretto
 ///
 /// `RistrettoComSig` utilises the [curve25519-dalek](https://github.com/dalek-cryptography/curve25519-dalek1)
@@ -99,6 +108,80 @@ use crate::{
 /// ```
 pub type RistrettoComSig = CommitmentSignature<RistrettoPublicKey, RistrettoSecretKey>;
 
+/// Prove knowledge of the opening, `(k, v)`, of `commitment` and bind the proof to `message`.
+///
+/// The challenge is \\( e = H(C || R || m) \\) so a proof made for one message (e.g. a verifier-supplied nonce) cannot
+/// be replayed against another. The signature nonces are derived deterministically from the opening, the commitment
+/// and the message.
+pub fn prove_commitment_ownership<C>(
+    factory: &C,
+    commitment: &HomomorphicCommitment<RistrettoPublicKey>,
+    k: &RistrettoSecretKey,
+    v: u64,
+    message: &[u8],
+) -> RistrettoComSig
+where
+    C: HomomorphicCommitmentFactory<P = RistrettoPublicKey>,
+{
+    let value = RistrettoSecretKey::from(v);
+    let nonce_x = ownership_nonce(k, &value, commitment, message, 0);
+    let nonce_a = ownership_nonce(k, &value, commitment, message, 1);
+    let public_nonce = factory.commit(&nonce_x, &nonce_a);
+    let e = ownership_challenge(commitment, &public_nonce, message);
+    RistrettoComSig::sign(&value, k, &nonce_a, &nonce_x, e.as_bytes(), factory)
+        .expect("A reduced challenge is always a valid scalar")
+}
+
+/// Returns true if `proof` shows knowledge of the opening of `commitment` and was made for `message`, as created by
+/// [prove_commitment_ownership].
+pub fn verify_commitment_ownership<C>(
+    factory: &C,
+    commitment: &HomomorphicCommitment<RistrettoPublicKey>,
+    message: &[u8],
+    proof: &RistrettoComSig,
+) -> bool
+where
+    C: HomomorphicCommitmentFactory<P = RistrettoPublicKey>,
+{
+    let e = ownership_challenge(commitment, proof.public_nonce(), message);
+    proof.verify(commitment, &e, factory)
+}
+
+/// Calculates the ownership challenge, \\( e = H(C || R || m) \\), reduced to a scalar
+fn ownership_challenge(
+    commitment: &HomomorphicCommitment<RistrettoPublicKey>,
+    public_nonce: &HomomorphicCommitment<RistrettoPublicKey>,
+    message: &[u8],
+) -> RistrettoSecretKey {
+    let hash = Blake256::with_params(&[], &[], OWNERSHIP_CHALLENGE_PERSONA)
+        .chain(commitment.as_bytes())
+        .chain(public_nonce.as_bytes())
+        .chain(message)
+        .finalize();
+    RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+}
+
+/// Derive the `index`th signature nonce from the commitment opening, the commitment and the message
+fn ownership_nonce(
+    k: &RistrettoSecretKey,
+    v: &RistrettoSecretKey,
+    commitment: &HomomorphicCommitment<RistrettoPublicKey>,
+    message: &[u8],
+    index: u8,
+) -> RistrettoSecretKey {
+    let hash = Blake2b::new()
+        .chain(OWNERSHIP_NONCE_LABEL)
+        .chain(k.as_bytes())
+        .chain(v.as_bytes())
+        .chain(commitment.as_bytes())
+        .chain(message)
+        .chain([index])
+        .finalize();
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&hash);
+    RistrettoSecretKey::from_uniform_bytes(&bytes)
+}
+
 #[cfg(test)]
 mod test {
     use digest::Digest;
@@ -110,6 +193,7 @@ mod test {
         keys::{PublicKey, SecretKey},
         ristretto::{
             pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+            ristretto_com_sig::{prove_commitment_ownership, verify_commitment_ownership},
             RistrettoComSig,
             RistrettoPublicKey,
             RistrettoSecretKey,
@@ -238,4 +322,50 @@ mod test {
         assert_eq!(bytes.capacity(), bytes.len());
         assert!(bytes.iter().all(|b| *b == 0x00));
     }
+
+    #[test]
+    fn commitment_ownership_round_trip() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let commitment = factory.commit_value(&k, 1_000);
+        let proof = prove_commitment_ownership(&factory, &commitment, &k, 1_000, b"claim #1");
+        assert!(verify_commitment_ownership(&factory, &commitment, b"claim #1", &proof));
+        // Proofs are deterministic for the same opening and message
+        let again = prove_commitment_ownership(&factory, &commitment, &k, 1_000, b"claim #1");
+        assert_eq!(proof, again);
+        // The proof does not verify against another commitment
+        let other = factory.commit_value(&k, 1_001);
+        assert!(!verify_commitment_ownership(&factory, &other, b"claim #1", &proof));
+        // Nor can it be made without the correct opening
+        let forged = prove_commitment_ownership(&factory, &commitment, &k, 999, b"claim #1");
+        assert!(!verify_commitment_ownership(
+            &factory,
+            &commitment,
+            b"claim #1",
+            &forged
+        ));
+    }
+
+    #[test]
+    fn commitment_ownership_is_bound_to_message() {
+        let mut rng = rand::thread_rng();
+        let factory = PedersenCommitmentFactory::default();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let commitment = factory.commit_value(&k, 42);
+        let proof = prove_commitment_ownership(&factory, &commitment, &k, 42, b"challenge from verifier");
+        assert!(verify_commitment_ownership(
+            &factory,
+            &commitment,
+            b"challenge from verifier",
+            &proof
+        ));
+        assert!(!verify_commitment_ownership(
+            &factory,
+            &commitment,
+            b"challenge from verifiex",
+            &proof
+        ));
+        assert!(!verify_commitment_ownership(&factory, &commitment, b"", &proof));
+    }
 }
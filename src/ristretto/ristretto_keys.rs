@@ -23,10 +23,13 @@
 //! The Tari-compatible implementation of Ristretto based on the curve25519-dalek implementation
 use std::{
     cmp::Ordering,
+    collections::HashMap,
+    convert::TryFrom,
     fmt,
     fmt::Debug,
     hash::{Hash, Hasher},
-    ops::{Add, Mul, Sub},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
 };
 
 use blake2::Blake2b;
@@ -34,15 +37,26 @@ use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_TABLE,
     ristretto::{CompressedRistretto, RistrettoPoint},
     scalar::Scalar,
-    traits::MultiscalarMul,
+    traits::{Identity, MultiscalarMul},
 };
 use digest::Digest;
 use once_cell::sync::OnceCell;
 use rand::{CryptoRng, Rng};
-use tari_utilities::{hex::Hex, ByteArray, ByteArrayError, Hashable};
-use zeroize::Zeroize;
+use subtle::{Choice, ConditionallySelectable};
+use tari_utilities::{
+    hex::{Hex, HexError},
+    ByteArray,
+    ByteArrayError,
+    Hashable,
+};
+use zeroize::{Zeroize, Zeroizing};
 
-use crate::keys::{DiffieHellmanSharedSecret, PublicKey, SecretKey};
+use crate::{
+    errors::KeyError,
+    hash::blake2::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey, SecretKey},
+    signatures::signature_scalar::canonical_scalar_from_bytes,
+};
 
 /// The [SecretKey](trait.SecretKey.html) implementation for [Ristretto](https://ristretto.group) is a thin wrapper
 /// around the Dalek [Scalar](struct.Scalar.html) type, representing a 256-bit integer (mod the group order).
@@ -82,6 +96,117 @@ impl SecretKey for RistrettoSecretKey {
     }
 }
 
+impl RistrettoSecretKey {
+    /// Reduce a hash digest (or any other little-endian byte string) to a scalar, mod the group order _l_.
+    ///
+    /// For 32-byte inputs this is exactly the reduction that [ByteArray::from_bytes] and
+    /// [SchnorrSignature::sign](crate::signatures::SchnorrSignature::sign) apply to a challenge, so challenges
+    /// constructed outside of `sign` will behave identically. Inputs of any other length are interpreted as a single
+    /// little-endian integer and reduced mod _l_; for 64-byte inputs this is the "wide" reduction of
+    /// [from_uniform_bytes](RistrettoSecretKey::from_uniform_bytes).
+    pub fn from_hash_bytes(bytes: &[u8]) -> RistrettoSecretKey {
+        // 2^256 mod l
+        let mut radix = [0u8; 64];
+        radix[32] = 1;
+        let radix = Scalar::from_bytes_mod_order_wide(&radix);
+        let k = bytes.chunks(32).rev().fold(Scalar::zero(), |acc, chunk| {
+            let mut a = [0u8; 32];
+            a[..chunk.len()].copy_from_slice(chunk);
+            acc * radix + Scalar::from_bytes_mod_order(a)
+        });
+        RistrettoSecretKey(k)
+    }
+
+    /// Hash `data` to a scalar using `Blake2b`. The 512-bit digest is reduced with a wide reduction, so the result is
+    /// uniformly distributed with negligible bias. See
+    /// [hash_to_scalar_with_digest](RistrettoSecretKey::hash_to_scalar_with_digest) to use a different hash function.
+    pub fn hash_to_scalar(data: &[u8]) -> RistrettoSecretKey {
+        RistrettoSecretKey::hash_to_scalar_with_digest::<Blake2b>(data)
+    }
+
+    /// Hash `data` to a scalar using the hash function `D`, reducing the digest with
+    /// [from_hash_bytes](RistrettoSecretKey::from_hash_bytes).
+    ///
+    /// A 512-bit digest gives an unbiased scalar. A 256-bit digest, such as `Blake256`, is supported for compatibility
+    /// with protocols that already reduce 32-byte hashes. Since _l_ is just over \\( 2^{252} \\), such scalars are
+    /// slightly biased towards small values, which is harmless for challenges but should be avoided when deriving
+    /// secret keys. The same input produces unrelated scalars with different hash functions.
+    pub fn hash_to_scalar_with_digest<D: Digest>(data: &[u8]) -> RistrettoSecretKey {
+        RistrettoSecretKey::from_hash_bytes(&D::digest(data))
+    }
+
+    /// Returns a copy of the canonical 32-byte little-endian encoding of the scalar. This runs in constant time.
+    ///
+    /// The returned array holds secret key material and is not cleared when it is dropped, so callers should zeroize
+    /// it (e.g. with [Zeroize::zeroize]) once it is no longer needed.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Consume the key and return its canonical 32-byte little-endian encoding, for exporting the key. The bytes are
+    /// wrapped in [Zeroizing], so they are cleared when the wrapper is dropped, and the key itself is zeroized as it
+    /// is consumed.
+    pub fn into_bytes(self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.0.to_bytes())
+    }
+
+    /// Generate a random, non-zero secret key using the operating system's RNG. This is a convenience for one-off key
+    /// generation, equivalent to `RistrettoSecretKey::random_non_zero(&mut OsRng)`. Use
+    /// [random](SecretKey::random) or [random_non_zero](SecretKey::random_non_zero) with an explicit RNG when
+    /// generating many keys, or where the source of randomness must be controlled, e.g. in tests.
+    ///
+    /// Only available with the `os_rng` feature.
+    #[cfg(feature = "os_rng")]
+    pub fn from_entropy() -> RistrettoSecretKey {
+        RistrettoSecretKey::random_non_zero(&mut rand::rngs::OsRng)
+    }
+
+    /// Import a secret key from any 32 bytes, e.g. a seed, by interpreting them as a little-endian integer and reducing
+    /// it mod _l_. This never fails, but values of _l_ and above alias smaller keys, so it must not be used where the
+    /// encoding is expected to be canonical; use the strict `TryFrom<&[u8]>` conversion to reject such input instead.
+    ///
+    /// Since 32 random bytes are not reduced uniformly, prefer
+    /// [from_uniform_bytes](RistrettoSecretKey::from_uniform_bytes) when deriving keys from random data.
+    pub fn from_bytes_mod_order(bytes: [u8; 32]) -> RistrettoSecretKey {
+        RistrettoSecretKey(Scalar::from_bytes_mod_order(bytes))
+    }
+
+    /// Map 64 uniformly random bytes to a secret key, using a wide reduction mod _l_. The resulting scalar has a
+    /// negligible bias, unlike reducing 32 random bytes, so this is the preferred way to derive keys from hash output
+    /// or other key material.
+    ///
+    /// This is [from_hash_bytes](RistrettoSecretKey::from_hash_bytes) restricted to 64-byte input.
+    pub fn from_uniform_bytes(bytes: &[u8; 64]) -> RistrettoSecretKey {
+        RistrettoSecretKey::from_hash_bytes(bytes)
+    }
+
+    /// Replace every key in `keys` with its multiplicative inverse mod _l_. Montgomery's trick is used, so the whole
+    /// batch costs a single inversion plus three multiplications per key, instead of one (expensive) inversion each.
+    ///
+    /// Returns `ZeroScalar`, leaving `keys` unchanged, if any of the keys is zero.
+    pub fn batch_invert(keys: &mut [RistrettoSecretKey]) -> Result<(), KeyError> {
+        if keys.iter().any(|k| k.0 == Scalar::zero()) {
+            return Err(KeyError::ZeroScalar);
+        }
+        let mut scalars = keys.iter().map(|k| k.0).collect::<Vec<_>>();
+        Scalar::batch_invert(&mut scalars);
+        for (k, inverse) in keys.iter_mut().zip(scalars.iter()) {
+            k.0 = *inverse;
+        }
+        scalars.zeroize();
+        Ok(())
+    }
+
+    /// Select `a` if `choice` is 0, or `b` if it is 1, in constant time. The selection is made with bitwise masking
+    /// rather than a branch, so neither the timing nor the memory access pattern depends on `choice` or on the keys.
+    ///
+    /// Secret keys zeroize themselves on drop, so they cannot be `Copy`, which `subtle::ConditionallySelectable`
+    /// requires; this inherent method provides the same operation.
+    pub fn conditional_select(a: &RistrettoSecretKey, b: &RistrettoSecretKey, choice: Choice) -> RistrettoSecretKey {
+        RistrettoSecretKey(Scalar::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
 impl Drop for RistrettoSecretKey {
     /// Clear the secret key value in memory when it goes out of scope
     fn drop(&mut self) {
@@ -112,6 +237,37 @@ impl ByteArray for RistrettoSecretKey {
     }
 }
 
+/// Convert a 32-byte little-endian slice into a secret key. Unlike [ByteArray::from_bytes], which silently reduces its
+/// input mod _l_, this conversion is strict and rejects any encoding that is not the canonical representation of a
+/// scalar, i.e. values greater than or equal to the group order.
+impl TryFrom<&[u8]> for RistrettoSecretKey {
+    type Error = ByteArrayError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        canonical_scalar_from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for RistrettoSecretKey {
+    /// The 32-byte little-endian scalar representation of the key
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Parse a secret key from a 64-character little-endian hex string, as produced by [Hex::to_hex]. Like
+/// [ByteArray::from_bytes], the value is reduced mod _l_.
+impl FromStr for RistrettoSecretKey {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+/// Secret keys can be stored in hash-based collections, and are hashed by their canonical 32-byte encoding. Note that
+/// hashing, and the lookups in `HashMap` and `HashSet` that rely on it, are **not** constant time, so doing so can leak
+/// information about the keys through timing side channels. Avoid keying collections on secret keys where that matters.
 impl Hash for RistrettoSecretKey {
     /// Require the implementation of the Hash trait for Hashmaps
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -125,7 +281,7 @@ impl PartialEq for RistrettoSecretKey {
     }
 }
 
-//----------------------------------   RistrettoSecretKey Mul / Add / Sub --------------------------------------------//
+//-------------------------------   RistrettoSecretKey Mul / Add / Sub / Neg -----------------------------------------//
 
 impl<'a, 'b> Mul<&'b RistrettoPublicKey> for &'a RistrettoSecretKey {
     type Output = RistrettoPublicKey;
@@ -136,6 +292,8 @@ impl<'a, 'b> Mul<&'b RistrettoPublicKey> for &'a RistrettoSecretKey {
     }
 }
 
+/// Returns `self + rhs` mod _l_, the order of the Ristretto group. This runs in constant time. The result is always a
+/// canonical scalar, so sums that exceed _l_ wrap around.
 impl<'a, 'b> Add<&'b RistrettoSecretKey> for &'a RistrettoSecretKey {
     type Output = RistrettoSecretKey;
 
@@ -145,6 +303,8 @@ impl<'a, 'b> Add<&'b RistrettoSecretKey> for &'a RistrettoSecretKey {
     }
 }
 
+/// Returns `self - rhs` mod _l_. This runs in constant time. If `rhs` is larger than `self`, the result wraps around
+/// to `l - (rhs - self)`.
 impl<'a, 'b> Sub<&'b RistrettoSecretKey> for &'a RistrettoSecretKey {
     type Output = RistrettoSecretKey;
 
@@ -169,6 +329,22 @@ define_mul_variants!(
     Output = RistrettoPublicKey
 );
 
+impl<'a> Neg for &'a RistrettoSecretKey {
+    type Output = RistrettoSecretKey;
+
+    fn neg(self) -> RistrettoSecretKey {
+        RistrettoSecretKey(-self.0)
+    }
+}
+
+impl Neg for RistrettoSecretKey {
+    type Output = RistrettoSecretKey;
+
+    fn neg(self) -> RistrettoSecretKey {
+        -&self
+    }
+}
+
 //---------------------------------------------      Conversions     -------------------------------------------------//
 
 impl From<u64> for RistrettoSecretKey {
@@ -228,6 +404,133 @@ impl RistrettoPublicKey {
     pub(super) fn compressed(&self) -> &CompressedRistretto {
         self.compressed.get_or_init(|| self.point.compress())
     }
+
+    /// Create a public key from its 32-byte compressed encoding, rejecting any encoding that is not canonical.
+    ///
+    /// The point is decompressed and then re-compressed, and the result must match the input exactly. Ristretto
+    /// decompression already rejects non-canonical encodings, so this is equivalent to [ByteArray::from_bytes] for
+    /// well-formed input; the explicit round trip check guarantees that two distinct byte strings can never decode to
+    /// the same key, which protocols that hash or compare serialized keys rely on.
+    ///
+    /// ## Why there is no x-coordinate and parity decoding
+    /// Some systems transmit an elliptic curve point as its x-coordinate plus a parity bit for y, and recover the
+    /// point with a "lift x" operation. Ristretto points are equivalence classes of curve points, so they do not have
+    /// a single well-defined x-coordinate to transmit. The Ristretto encoding is already a compact, canonical 32-byte
+    /// encoding of a single field element, and decoding it is the closest equivalent: there is exactly one valid
+    /// encoding per point, and byte strings that do not correspond to a point (non-canonical or negative field
+    /// elements, or values with no square root on the curve) are rejected with an error.
+    pub fn from_canonical_bytes(bytes: &[u8; 32]) -> Result<RistrettoPublicKey, ByteArrayError> {
+        let invalid = || ByteArrayError::ConversionError("Non-canonical Ristretto point encoding".to_string());
+        let point = CompressedRistretto(*bytes).decompress().ok_or_else(invalid)?;
+        let compressed = point.compress();
+        if compressed.as_bytes() != bytes {
+            return Err(invalid());
+        }
+        Ok(Self {
+            point,
+            compressed: compressed.into(),
+        })
+    }
+
+    /// Deterministically derive a keypair from a 32-byte seed. The seed is hashed with `Blake2b` and the digest is
+    /// mapped to a scalar using [RistrettoSecretKey::from_uniform_bytes]. The same seed always produces the same
+    /// keypair, which is useful for test vectors and deterministic protocols, but the seed must be kept as secret as
+    /// the key itself.
+    ///
+    /// The derived secret key is never zero.
+    pub fn keypair_from_seed(seed: &[u8; 32]) -> (RistrettoSecretKey, RistrettoPublicKey) {
+        let mut counter = 0u64;
+        loop {
+            let mut hash = [0u8; 64];
+            let digest = Blake2b::new()
+                .chain(b"tari.keypair_from_seed")
+                .chain(seed)
+                .chain(counter.to_le_bytes())
+                .finalize();
+            hash.copy_from_slice(&digest);
+            let k = RistrettoSecretKey::from_uniform_bytes(&hash);
+            // A zero secret key occurs with negligible probability, but is never acceptable
+            if k.0 != Scalar::zero() {
+                let pk = RistrettoPublicKey::from_secret_key(&k);
+                return (k, pk);
+            }
+            counter += 1;
+        }
+    }
+
+    /// Multiply this key by a hash digest, reduced to a scalar with [RistrettoSecretKey::from_hash_bytes]. For
+    /// 64-byte digests this is a wide reduction, identical to [RistrettoSecretKey::from_uniform_bytes].
+    pub fn mul_hash(&self, hash: &[u8]) -> RistrettoPublicKey {
+        let k = RistrettoSecretKey::from_hash_bytes(hash);
+        RistrettoPublicKey::new_from_pk(k.0 * self.point)
+    }
+
+    /// For each of the `targets`, find the index of the secret key in `candidates` that generates it, or `None` if
+    /// there is no such candidate. If several candidates match a target, the first one is returned.
+    ///
+    /// Each candidate is multiplied by the generator exactly once, regardless of how many targets there are, so this
+    /// is considerably faster than checking every target against every candidate when scanning for owned keys.
+    pub fn find_matching_secret(
+        targets: &[RistrettoPublicKey],
+        candidates: &[RistrettoSecretKey],
+    ) -> Vec<Option<usize>> {
+        let mut lookup = HashMap::with_capacity(candidates.len());
+        for (i, k) in candidates.iter().enumerate() {
+            let p = (&k.0 * &RISTRETTO_BASEPOINT_TABLE).compress();
+            lookup.entry(p.to_bytes()).or_insert(i);
+        }
+        targets
+            .iter()
+            .map(|t| lookup.get(t.compressed().as_bytes()).copied())
+            .collect()
+    }
+
+    /// Aggregate a set of public keys in a way that is not susceptible to rogue key attacks. The aggregate key is
+    /// $$
+    ///   L = H(P_1 || P_2 || \dots || P_n), \quad a_i = H(L || P_i), \quad P_{agg} = \sum a_i P_i
+    /// $$
+    /// where the keys in _L_ are sorted according to their canonical (compressed) ordering, so the aggregate key does
+    /// not depend on the order of `keys`.
+    ///
+    /// The coefficients are returned in the same order as `keys`, so that the owner of `keys[i]` can calculate their
+    /// share of the aggregate secret key, `coefficients[i] * k_i`.
+    pub fn aggregate(keys: &[RistrettoPublicKey]) -> (RistrettoPublicKey, Vec<RistrettoSecretKey>) {
+        let mut sorted: Vec<&RistrettoPublicKey> = keys.iter().collect();
+        sorted.sort_unstable();
+        let common = sorted
+            .iter()
+            .fold(Blake256::new(), |hasher, k| hasher.chain(k.as_bytes()))
+            .finalize();
+        let coefficients: Vec<RistrettoSecretKey> = keys
+            .iter()
+            .map(|k| {
+                let a = Blake256::new().chain(&common).chain(k.as_bytes()).finalize();
+                RistrettoSecretKey::from_bytes(&a).expect("Blake256 produces a 32 byte digest")
+            })
+            .collect();
+        let aggregate = RistrettoPublicKey::batch_mul(&coefficients, keys);
+        (aggregate, coefficients)
+    }
+
+    /// Derive the public key \\( P = k.B \\) for an arbitrary base point `base`, rather than the Ristretto generator,
+    /// e.g. for blinded or tweaked keys. With the generator as the base this is the same as
+    /// [from_secret_key](PublicKey::from_secret_key), but slower, since it cannot use the precomputed basepoint table.
+    pub fn from_secret_key_with_base(secret: &RistrettoSecretKey, base: &RistrettoPublicKey) -> RistrettoPublicKey {
+        secret * base
+    }
+
+    /// Aggregate public keys by summing them in a single pass, \\( P = \sum P_i \\). An empty slice sums to the
+    /// identity.
+    pub fn from_sum(keys: &[RistrettoPublicKey]) -> RistrettoPublicKey {
+        RistrettoPublicKey::new_from_pk(keys.iter().map(|k| k.point).sum())
+    }
+
+    /// Select `a` if `choice` is 0, or `b` if it is 1, in constant time, as for
+    /// [RistrettoSecretKey::conditional_select]. Only the point is selected; the compressed encoding of the result is
+    /// computed afresh when it is first needed.
+    pub fn conditional_select(a: &RistrettoPublicKey, b: &RistrettoPublicKey, choice: Choice) -> RistrettoPublicKey {
+        RistrettoPublicKey::new_from_pk(RistrettoPoint::conditional_select(&a.point, &b.point, choice))
+    }
 }
 
 impl PublicKey for RistrettoPublicKey {
@@ -249,6 +552,11 @@ impl PublicKey for RistrettoPublicKey {
         let p = RistrettoPoint::multiscalar_mul(s, p);
         RistrettoPublicKey::new_from_pk(p)
     }
+
+    /// Returns true if this key is the identity point, i.e. the "point at infinity"
+    fn is_identity(&self) -> bool {
+        self.point == RistrettoPoint::identity()
+    }
 }
 
 impl DiffieHellmanSharedSecret for RistrettoPublicKey {
@@ -267,6 +575,8 @@ impl Hashable for RistrettoPublicKey {
     }
 }
 
+/// Public keys are hashed by their compressed encoding, which is consistent with `Eq`, so keys can be deduplicated in
+/// a `HashSet` or used as `HashMap` keys. [CompressedRistrettoPublicKey] hashes identically.
 impl Hash for RistrettoPublicKey {
     /// Require the implementation of the Hash trait for Hashmaps
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -296,6 +606,16 @@ impl fmt::Debug for RistrettoPublicKey {
     }
 }
 
+/// Formats the key as 64 lowercase hex characters, as for `Display`. The alternate flag (`{:#x}`) adds a `0x` prefix.
+impl fmt::LowerHex for RistrettoPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", self.to_hex())
+    }
+}
+
 //------------------------------------ PublicKey PartialEq, Eq, Ord impl ---------------------------------------------//
 
 impl PartialEq for RistrettoPublicKey {
@@ -310,11 +630,14 @@ impl Eq for RistrettoPublicKey {}
 
 impl PartialOrd for RistrettoPublicKey {
     fn partial_cmp(&self, other: &RistrettoPublicKey) -> Option<Ordering> {
-        self.compressed().as_bytes().partial_cmp(other.compressed().as_bytes())
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for RistrettoPublicKey {
+    /// Public keys are ordered by comparing their 32-byte compressed encodings lexicographically. This is a canonical
+    /// total order: it depends only on the encoded point, so it is preserved across serialization round trips and can
+    /// be used to deterministically sort keys, e.g. for key aggregation.
     fn cmp(&self, other: &Self) -> Ordering {
         self.compressed().as_bytes().cmp(other.compressed().as_bytes())
     }
@@ -348,6 +671,33 @@ impl ByteArray for RistrettoPublicKey {
     }
 }
 
+/// Convert a 32-byte compressed Ristretto encoding into a public key. The same validation rules as
+/// [ByteArray::from_bytes] apply, so non-canonical encodings are rejected.
+impl TryFrom<&[u8]> for RistrettoPublicKey {
+    type Error = ByteArrayError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        RistrettoPublicKey::from_bytes(bytes)
+    }
+}
+
+impl AsRef<[u8]> for RistrettoPublicKey {
+    /// The 32-byte compressed representation of the key
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Parse a public key from the hex string of its 32-byte compressed representation, as produced by [Hex::to_hex] and
+/// `Display`.
+impl FromStr for RistrettoPublicKey {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
 //----------------------------------         PublicKey Add / Sub / Mul   ---------------------------------------------//
 
 impl<'a, 'b> Add<&'b RistrettoPublicKey> for &'a RistrettoPublicKey {
@@ -368,6 +718,38 @@ impl<'a, 'b> Sub<&'b RistrettoPublicKey> for &'a RistrettoPublicKey {
     }
 }
 
+impl<'a> Neg for &'a RistrettoPublicKey {
+    type Output = RistrettoPublicKey;
+
+    fn neg(self) -> RistrettoPublicKey {
+        RistrettoPublicKey::new_from_pk(-self.point)
+    }
+}
+
+impl Neg for RistrettoPublicKey {
+    type Output = RistrettoPublicKey;
+
+    fn neg(self) -> RistrettoPublicKey {
+        -&self
+    }
+}
+
+/// Add a public key in place. The cached compressed encoding is cleared, since it no longer matches the point.
+impl<'a> AddAssign<&'a RistrettoPublicKey> for RistrettoPublicKey {
+    fn add_assign(&mut self, rhs: &'a RistrettoPublicKey) {
+        self.point += rhs.point;
+        self.compressed = OnceCell::new();
+    }
+}
+
+/// Subtract a public key in place. The cached compressed encoding is cleared, since it no longer matches the point.
+impl<'a> SubAssign<&'a RistrettoPublicKey> for RistrettoPublicKey {
+    fn sub_assign(&mut self, rhs: &'a RistrettoPublicKey) {
+        self.point -= rhs.point;
+        self.compressed = OnceCell::new();
+    }
+}
+
 impl<'a, 'b> Mul<&'b RistrettoSecretKey> for &'a RistrettoPublicKey {
     type Output = RistrettoPublicKey;
 
@@ -377,6 +759,7 @@ impl<'a, 'b> Mul<&'b RistrettoSecretKey> for &'a RistrettoPublicKey {
     }
 }
 
+/// Returns `self * rhs` mod _l_. This runs in constant time.
 impl<'a, 'b> Mul<&'b RistrettoSecretKey> for &'a RistrettoSecretKey {
     type Output = RistrettoSecretKey;
 
@@ -433,16 +816,118 @@ impl From<RistrettoPublicKey> for CompressedRistretto {
     }
 }
 
+//---------------------------------------   Compressed Ristretto Public Key   ----------------------------------------//
+
+/// A Ristretto public key held in its 32-byte compressed form. The point is only decompressed when it is first needed,
+/// and the result is cached, so collections of keys that are mostly hashed or compared byte-wise avoid the cost of
+/// repeated compression and decompression.
+///
+/// The bytes are not validated on construction; [point](CompressedRistrettoPublicKey::point) returns `None` and
+/// conversion into a [RistrettoPublicKey] fails if they are not a valid point encoding.
+#[derive(Clone)]
+pub struct CompressedRistrettoPublicKey {
+    compressed: CompressedRistretto,
+    point: OnceCell<Option<RistrettoPoint>>,
+}
+
+impl CompressedRistrettoPublicKey {
+    /// Wrap the given compressed point encoding
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self {
+            compressed: CompressedRistretto(bytes),
+            point: OnceCell::new(),
+        }
+    }
+
+    /// The compressed encoding of the public key
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.compressed.as_bytes()
+    }
+
+    /// The decompressed point, or `None` if the bytes are not a valid Ristretto point. The point is decompressed on
+    /// the first call and cached thereafter.
+    pub fn point(&self) -> Option<&RistrettoPoint> {
+        self.point.get_or_init(|| self.compressed.decompress()).as_ref()
+    }
+}
+
+impl From<&RistrettoPublicKey> for CompressedRistrettoPublicKey {
+    fn from(pk: &RistrettoPublicKey) -> Self {
+        Self {
+            compressed: *pk.compressed(),
+            point: OnceCell::from(Some(pk.point)),
+        }
+    }
+}
+
+impl From<RistrettoPublicKey> for CompressedRistrettoPublicKey {
+    fn from(pk: RistrettoPublicKey) -> Self {
+        Self::from(&pk)
+    }
+}
+
+impl TryFrom<&CompressedRistrettoPublicKey> for RistrettoPublicKey {
+    type Error = ByteArrayError;
+
+    fn try_from(pk: &CompressedRistrettoPublicKey) -> Result<Self, Self::Error> {
+        let point = pk
+            .point()
+            .ok_or_else(|| ByteArrayError::ConversionError("Invalid compressed Ristretto point".to_string()))?;
+        Ok(Self {
+            point: *point,
+            compressed: pk.compressed.into(),
+        })
+    }
+}
+
+impl TryFrom<CompressedRistrettoPublicKey> for RistrettoPublicKey {
+    type Error = ByteArrayError;
+
+    fn try_from(pk: CompressedRistrettoPublicKey) -> Result<Self, Self::Error> {
+        Self::try_from(&pk)
+    }
+}
+
+impl PartialEq for CompressedRistrettoPublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.compressed == other.compressed
+    }
+}
+
+impl Eq for CompressedRistrettoPublicKey {}
+
+impl Hash for CompressedRistrettoPublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl fmt::Debug for CompressedRistrettoPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.compressed.as_bytes().to_vec().to_hex())
+    }
+}
+
 //--------------------------------------------------------------------------------------------------------------------//
 //                                                     Tests                                                          //
 //--------------------------------------------------------------------------------------------------------------------//
 
 #[cfg(test)]
 mod test {
-    use tari_utilities::{message_format::MessageFormat, ByteArray};
+    use std::collections::{hash_map::DefaultHasher, HashSet};
+
+    use curve25519_dalek::constants::{BASEPOINT_ORDER, RISTRETTO_BASEPOINT_POINT};
+    use rand::{CryptoRng, RngCore};
+    use tari_utilities::{hex::from_hex, message_format::MessageFormat, ByteArray};
 
     use super::*;
-    use crate::{keys::PublicKey, ristretto::test_common::get_keypair};
+    use crate::{
+        keys::PublicKey,
+        ristretto::{
+            constants::RISTRETTO_NUMS_POINTS,
+            test_common::{get_keypair, seeded_rng},
+        },
+    };
 
     fn assert_completely_equal(k1: &RistrettoPublicKey, k2: &RistrettoPublicKey) {
         assert_eq!(k1, k2);
@@ -450,6 +935,72 @@ mod test {
         assert_eq!(k1.compressed, k2.compressed);
     }
 
+    /// Yields zero bytes for the first `zero_fills` calls to `fill_bytes`, and defers to `rng` afterwards
+    struct ZeroFirstRng<R> {
+        zero_fills: usize,
+        rng: R,
+    }
+
+    impl<R: RngCore> RngCore for ZeroFirstRng<R> {
+        fn next_u32(&mut self) -> u32 {
+            self.rng.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.rng.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            if self.zero_fills > 0 {
+                self.zero_fills -= 1;
+                dest.iter_mut().for_each(|b| *b = 0);
+            } else {
+                self.rng.fill_bytes(dest);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl<R: CryptoRng> CryptoRng for ZeroFirstRng<R> {}
+
+    #[test]
+    fn random_non_zero_skips_zero() {
+        let mut rng = ZeroFirstRng {
+            zero_fills: 1,
+            rng: seeded_rng(0x5eed),
+        };
+        assert_eq!(RistrettoSecretKey::random(&mut rng), RistrettoSecretKey::default());
+
+        let mut rng = ZeroFirstRng {
+            zero_fills: 2,
+            rng: seeded_rng(0x5eed),
+        };
+        let k = RistrettoSecretKey::random_non_zero(&mut rng);
+        assert_ne!(k, RistrettoSecretKey::default());
+        assert_eq!(rng.zero_fills, 0);
+
+        let mut rng = ZeroFirstRng {
+            zero_fills: 1,
+            rng: seeded_rng(0x5eed),
+        };
+        let (k, pk) = RistrettoPublicKey::random_keypair(&mut rng);
+        assert_ne!(k, RistrettoSecretKey::default());
+        assert!(!pk.is_identity());
+    }
+
+    #[cfg(feature = "os_rng")]
+    #[test]
+    fn from_entropy() {
+        let k1 = RistrettoSecretKey::from_entropy();
+        let k2 = RistrettoSecretKey::from_entropy();
+        assert_ne!(k1, k2);
+        assert_ne!(k1, RistrettoSecretKey::default());
+    }
+
     #[test]
     fn test_generation() {
         let mut rng = rand::thread_rng();
@@ -601,6 +1152,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn canonical_bytes() {
+        let (_, pk) = get_keypair();
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(pk.as_bytes());
+        let pk2 = RistrettoPublicKey::from_canonical_bytes(&bytes).unwrap();
+        assert_completely_equal(&pk, &pk2);
+        let non_canonical = [
+            // Non-canonical field encodings
+            "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // Negative field element
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            // s = -1
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ];
+        for encoding in &non_canonical {
+            bytes.copy_from_slice(&from_hex(encoding).unwrap());
+            assert!(RistrettoPublicKey::from_canonical_bytes(&bytes).is_err());
+            assert!(RistrettoPublicKey::from_bytes(&bytes).is_err());
+        }
+    }
+
     #[test]
     fn mul() {
         let (k, p) = get_keypair();
@@ -645,6 +1219,27 @@ mod test {
         }
     }
 
+    #[test]
+    fn into_bytes() {
+        let mut rng = rand::thread_rng();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let expected = k.to_bytes();
+        let bytes = k.into_bytes();
+        assert_eq!(*bytes, expected);
+        let ptr;
+        {
+            let bytes = RistrettoSecretKey::random(&mut rng).into_bytes();
+            ptr = bytes.as_ptr();
+        }
+        // As above, this can only be checked reliably in debug mode
+        if cfg!(debug_assertions) {
+            unsafe {
+                use std::slice;
+                assert_eq!(slice::from_raw_parts(ptr, 32), &[0u8; 32][..]);
+            }
+        }
+    }
+
     #[test]
     fn convert_from_u64() {
         let k = RistrettoSecretKey::from(42u64);
@@ -667,11 +1262,15 @@ mod test {
     #[test]
     fn serialize_deserialize_base64() {
         let mut rng = rand::thread_rng();
-        let (k, pk) = RistrettoPublicKey::random_keypair(&mut rng);
-        let ser_k = k.to_base64().unwrap();
+        let (_, pk) = RistrettoPublicKey::random_keypair(&mut rng);
+        #[cfg(feature = "serde-secrets")]
+        {
+            let k = RistrettoSecretKey::random(&mut rng);
+            let ser_k = k.to_base64().unwrap();
+            let k2: RistrettoSecretKey = RistrettoSecretKey::from_base64(&ser_k).unwrap();
+            assert_eq!(k, k2, "Deserialised secret key");
+        }
         let ser_pk = pk.to_base64().unwrap();
-        let k2: RistrettoSecretKey = RistrettoSecretKey::from_base64(&ser_k).unwrap();
-        assert_eq!(k, k2, "Deserialised secret key");
         let pk2: RistrettoPublicKey = RistrettoPublicKey::from_base64(&ser_pk).unwrap();
         assert_completely_equal(&pk, &pk2);
     }
@@ -679,12 +1278,16 @@ mod test {
     #[test]
     fn serialize_deserialize_json() {
         let mut rng = rand::thread_rng();
-        let (k, pk) = RistrettoPublicKey::random_keypair(&mut rng);
-        let ser_k = k.to_json().unwrap();
+        let (_, pk) = RistrettoPublicKey::random_keypair(&mut rng);
+        #[cfg(feature = "serde-secrets")]
+        {
+            let k = RistrettoSecretKey::random(&mut rng);
+            let ser_k = k.to_json().unwrap();
+            let k2: RistrettoSecretKey = RistrettoSecretKey::from_json(&ser_k).unwrap();
+            assert_eq!(k, k2, "Deserialised secret key");
+        }
         let ser_pk = pk.to_json().unwrap();
-        println!("JSON pubkey: {} privkey: {}", ser_pk, ser_k);
-        let k2: RistrettoSecretKey = RistrettoSecretKey::from_json(&ser_k).unwrap();
-        assert_eq!(k, k2, "Deserialised secret key");
+        println!("JSON pubkey: {}", ser_pk);
         let pk2: RistrettoPublicKey = RistrettoPublicKey::from_json(&ser_pk).unwrap();
         assert_completely_equal(&pk, &pk2);
     }
@@ -692,11 +1295,15 @@ mod test {
     #[test]
     fn serialize_deserialize_binary() {
         let mut rng = rand::thread_rng();
-        let (k, pk) = RistrettoPublicKey::random_keypair(&mut rng);
-        let ser_k = k.to_binary().unwrap();
+        let (_, pk) = RistrettoPublicKey::random_keypair(&mut rng);
+        #[cfg(feature = "serde-secrets")]
+        {
+            let k = RistrettoSecretKey::random(&mut rng);
+            let ser_k = k.to_binary().unwrap();
+            let k2: RistrettoSecretKey = RistrettoSecretKey::from_binary(&ser_k).unwrap();
+            assert_eq!(k, k2);
+        }
         let ser_pk = pk.to_binary().unwrap();
-        let k2: RistrettoSecretKey = RistrettoSecretKey::from_binary(&ser_k).unwrap();
-        assert_eq!(k, k2);
         let pk2: RistrettoPublicKey = RistrettoPublicKey::from_binary(&ser_pk).unwrap();
         assert_completely_equal(&pk, &pk2);
     }
@@ -708,4 +1315,467 @@ mod test {
         assert_eq!(format!("{}", pk), hex);
         assert_eq!(format!("{:?}", pk), hex);
     }
+
+    #[test]
+    fn aggregate_is_order_independent() {
+        let keys: Vec<(RistrettoSecretKey, RistrettoPublicKey)> = (0..4).map(|_| get_keypair()).collect();
+        let pub_keys: Vec<RistrettoPublicKey> = keys.iter().map(|(_, p)| p.clone()).collect();
+        let mut reversed = pub_keys.clone();
+        reversed.reverse();
+        let (agg, coefficients) = RistrettoPublicKey::aggregate(&pub_keys);
+        let (agg_rev, coefficients_rev) = RistrettoPublicKey::aggregate(&reversed);
+        assert_eq!(agg, agg_rev);
+        let n = coefficients.len();
+        for i in 0..n {
+            assert_eq!(coefficients[i], coefficients_rev[n - 1 - i]);
+        }
+        // The coefficients transform each secret key into its share of the aggregate secret key
+        let agg_secret = keys
+            .iter()
+            .zip(coefficients.iter())
+            .fold(RistrettoSecretKey::default(), |sum, ((k, _), a)| sum + a * k);
+        assert_eq!(RistrettoPublicKey::from_secret_key(&agg_secret), agg);
+        // Aggregation is not a simple sum
+        let naive = pub_keys.iter().fold(RistrettoPublicKey::default(), |sum, p| sum + p);
+        assert_ne!(agg, naive);
+    }
+
+    #[test]
+    fn scalar_arithmetic_is_mod_l() {
+        let (a, _) = get_keypair();
+        let (b, _) = get_keypair();
+        assert_eq!(&(&a - &b) + &b, a);
+        assert_eq!(&a * &b, RistrettoSecretKey(a.0 * b.0));
+        // 0 - 1 wraps around to l - 1
+        let zero = RistrettoSecretKey::default();
+        let one = RistrettoSecretKey::from(1u64);
+        let l_minus_one = &zero - &one;
+        assert_eq!(
+            l_minus_one.to_hex(),
+            "ecd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010"
+        );
+        // and (l - 1) + 1 wraps back around to zero
+        assert_eq!(&l_minus_one + &one, zero);
+        assert_eq!(&l_minus_one * &l_minus_one, one);
+    }
+
+    #[test]
+    fn reduce_hash_bytes() {
+        let m = [0xffu8; 32];
+        assert_eq!(
+            RistrettoSecretKey::from_hash_bytes(&m),
+            RistrettoSecretKey::from_bytes(&m).unwrap()
+        );
+        let mut wide = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut wide);
+        assert_eq!(
+            RistrettoSecretKey::from_hash_bytes(&wide),
+            RistrettoSecretKey(Scalar::from_bytes_mod_order_wide(&wide))
+        );
+        assert_eq!(RistrettoSecretKey::from_hash_bytes(&[]), RistrettoSecretKey::default());
+        assert_eq!(
+            RistrettoSecretKey::from_hash_bytes(&[42]),
+            RistrettoSecretKey::from(42u64)
+        );
+    }
+
+    #[test]
+    fn sort_order_survives_serialization() {
+        let mut keys: Vec<RistrettoPublicKey> = (0..20).map(|_| get_keypair().1).collect();
+        keys.sort();
+        for pair in keys.windows(2) {
+            assert!(pair[0].as_bytes() < pair[1].as_bytes());
+        }
+        let mut restored: Vec<RistrettoPublicKey> = keys
+            .iter()
+            .rev()
+            .map(|k| RistrettoPublicKey::from_binary(&k.to_binary().unwrap()).unwrap())
+            .collect();
+        restored.sort();
+        assert_eq!(keys, restored);
+    }
+
+    #[test]
+    fn identity() {
+        assert!(RistrettoPublicKey::default().is_identity());
+        let (k, p) = get_keypair();
+        assert!(!p.is_identity());
+        assert!((&p - &p).is_identity());
+        assert!(!RistrettoPublicKey::from_secret_key(&k).is_identity());
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let (k, pk) = get_keypair();
+        let k2 = RistrettoSecretKey::try_from(k.as_ref()).unwrap();
+        assert_eq!(k, k2);
+        let pk2 = RistrettoPublicKey::try_from(pk.as_ref()).unwrap();
+        assert_completely_equal(&pk, &pk2);
+        // Wrong lengths
+        assert!(matches!(
+            RistrettoSecretKey::try_from(&[1u8; 31][..]),
+            Err(ByteArrayError::IncorrectLength)
+        ));
+        assert!(matches!(
+            RistrettoPublicKey::try_from(&[1u8; 33][..]),
+            Err(ByteArrayError::IncorrectLength)
+        ));
+        // The group order, l, is not a canonical scalar encoding, even though `from_bytes` will happily reduce it
+        let l = BASEPOINT_ORDER.to_bytes();
+        assert!(RistrettoSecretKey::try_from(&l[..]).is_err());
+        assert_eq!(
+            RistrettoSecretKey::from_bytes(&l).unwrap(),
+            RistrettoSecretKey::default()
+        );
+        // Non-canonical field encoding
+        let bad = from_hex("00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+        assert!(RistrettoPublicKey::try_from(&bad[..]).is_err());
+    }
+
+    #[test]
+    fn from_uniform_bytes() {
+        let mut bytes = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let k = RistrettoSecretKey::from_uniform_bytes(&bytes);
+        assert_eq!(k, RistrettoSecretKey(Scalar::from_bytes_mod_order_wide(&bytes)));
+        // 2^256 reduces to 2^256 mod l, rather than zero
+        let mut radix = [0u8; 64];
+        radix[32] = 1;
+        assert_ne!(
+            RistrettoSecretKey::from_uniform_bytes(&radix),
+            RistrettoSecretKey::default()
+        );
+    }
+
+    #[test]
+    fn hash_to_scalar() {
+        let data = b"hash to scalar";
+        let wide = RistrettoSecretKey::hash_to_scalar(data);
+        assert_eq!(wide, RistrettoSecretKey::hash_to_scalar_with_digest::<Blake2b>(data));
+        let mut digest = [0u8; 64];
+        digest.copy_from_slice(&Blake2b::digest(data));
+        assert_eq!(wide, RistrettoSecretKey::from_uniform_bytes(&digest));
+        let narrow = RistrettoSecretKey::hash_to_scalar_with_digest::<Blake256>(data);
+        assert_eq!(narrow, RistrettoSecretKey::from_bytes(&Blake256::digest(data)).unwrap());
+        assert_ne!(wide, narrow);
+        assert_ne!(wide, RistrettoSecretKey::hash_to_scalar(b"hash to scalar!"));
+    }
+
+    #[test]
+    fn keypair_from_seed() {
+        let seed = [42u8; 32];
+        let (k1, p1) = RistrettoPublicKey::keypair_from_seed(&seed);
+        let (k2, p2) = RistrettoPublicKey::keypair_from_seed(&seed);
+        assert_eq!(k1, k2);
+        assert_eq!(p1, p2);
+        assert_eq!(p1, RistrettoPublicKey::from_secret_key(&k1));
+        assert_ne!(k1, RistrettoSecretKey::default());
+        let (k3, p3) = RistrettoPublicKey::keypair_from_seed(&[43u8; 32]);
+        assert_ne!(k1, k3);
+        assert_ne!(p1, p3);
+    }
+
+    #[test]
+    fn compressed_public_key() {
+        let (_, pk) = get_keypair();
+        let compressed = CompressedRistrettoPublicKey::from(&pk);
+        assert_eq!(compressed.as_bytes(), pk.as_bytes());
+        let pk2 = RistrettoPublicKey::try_from(&compressed).unwrap();
+        assert_completely_equal(&pk, &pk2);
+        // A lazily decompressed point matches an eager decompression
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(pk.as_bytes());
+        let lazy = CompressedRistrettoPublicKey::new(bytes);
+        assert_eq!(lazy, compressed);
+        assert_eq!(lazy.point(), Some(&CompressedRistretto(bytes).decompress().unwrap()));
+        assert_eq!(lazy.point(), Some(&pk.point));
+        let pk3 = RistrettoPublicKey::try_from(lazy).unwrap();
+        assert_completely_equal(&pk, &pk3);
+        // Invalid points are only detected on decompression
+        let bad = CompressedRistrettoPublicKey::new([0xff; 32]);
+        assert!(bad.point().is_none());
+        assert!(RistrettoPublicKey::try_from(&bad).is_err());
+    }
+
+    #[test]
+    fn find_matching_secret() {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<(RistrettoSecretKey, RistrettoPublicKey)> =
+            (0..5).map(|_| RistrettoPublicKey::random_keypair(&mut rng)).collect();
+        let (_, stranger) = RistrettoPublicKey::random_keypair(&mut rng);
+        let candidates = vec![
+            keys[3].0.clone(),
+            keys[0].0.clone(),
+            keys[4].0.clone(),
+            keys[1].0.clone(),
+        ];
+        let targets = vec![
+            keys[0].1.clone(),
+            keys[1].1.clone(),
+            keys[2].1.clone(),
+            stranger,
+            keys[4].1.clone(),
+            keys[3].1.clone(),
+        ];
+        let matches = RistrettoPublicKey::find_matching_secret(&targets, &candidates);
+        assert_eq!(matches, vec![Some(1), Some(3), None, None, Some(2), Some(0)]);
+        assert!(RistrettoPublicKey::find_matching_secret(&targets, &[])
+            .iter()
+            .all(Option::is_none));
+        assert!(RistrettoPublicKey::find_matching_secret(&[], &candidates).is_empty());
+    }
+
+    #[test]
+    fn from_str() {
+        let (k, pk) = get_keypair();
+        let k2 = k.to_hex().parse::<RistrettoSecretKey>().unwrap();
+        assert_eq!(k, k2);
+        let pk2 = pk.to_string().parse::<RistrettoPublicKey>().unwrap();
+        assert_completely_equal(&pk, &pk2);
+        let pk3 =
+            RistrettoPublicKey::from_str("e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e").unwrap();
+        assert_eq!(
+            pk3.to_hex(),
+            "e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e"
+        );
+        // Wrong lengths
+        assert!("e882b131016b52c1d3337080187cf768"
+            .parse::<RistrettoPublicKey>()
+            .is_err());
+        assert!("e882b131016b52c1d3337080187cf768"
+            .parse::<RistrettoSecretKey>()
+            .is_err());
+        assert!("".parse::<RistrettoSecretKey>().is_err());
+        // Invalid hex characters
+        let bad = "g882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e";
+        assert!(bad.parse::<RistrettoPublicKey>().is_err());
+        assert!(bad.parse::<RistrettoSecretKey>().is_err());
+        // Valid hex, but not a valid point
+        let bad = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+        assert!(bad.parse::<RistrettoPublicKey>().is_err());
+    }
+
+    #[test]
+    fn public_keys_in_hash_set() {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<RistrettoPublicKey> = (0..10)
+            .map(|_| RistrettoPublicKey::random_keypair(&mut rng).1)
+            .collect();
+        let mut set = HashSet::new();
+        for k in keys.iter().chain(keys.iter()) {
+            set.insert(k.clone());
+        }
+        assert_eq!(set.len(), keys.len());
+        // Keys with and without a cached compressed representation are the same set member
+        let uncached = RistrettoPublicKey::new_from_pk(RistrettoPoint::from(&keys[0]));
+        assert!(!set.insert(uncached));
+        assert!(keys.iter().all(|k| set.contains(k)));
+        // Compressed keys hash identically to their decompressed counterparts
+        let hash = |k: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            k(&mut hasher);
+            hasher.finish()
+        };
+        let compressed = CompressedRistrettoPublicKey::from(&keys[0]);
+        assert_eq!(hash(&|h| Hash::hash(&keys[0], h)), hash(&|h| compressed.hash(h)));
+    }
+
+    #[test]
+    fn mul_hash() {
+        let (_, pk) = get_keypair();
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(&Blake2b::digest(b"Unseen Academicals"));
+        let k = RistrettoSecretKey::from_uniform_bytes(&hash);
+        assert_eq!(pk.mul_hash(&hash), &pk * &k);
+        // 32-byte digests are reduced in the same way as from_bytes
+        let hash = Blake256::digest(b"Unseen Academicals");
+        assert_eq!(
+            pk.mul_hash(&hash),
+            &pk * &RistrettoSecretKey::from_bytes(&hash).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_off_curve_bytes() {
+        let decode = |hex: &str| {
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(&from_hex(hex).unwrap());
+            RistrettoPublicKey::from_canonical_bytes(&bytes)
+        };
+        // 5 * G
+        let p = decode("e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e").unwrap();
+        assert_eq!(p, RistrettoPublicKey::from_secret_key(&RistrettoSecretKey::from(5)));
+        let bad = [
+            // Non-canonical field element
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // Negative field element
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            // Not on the curve: x^2 has no square root
+            "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+            // s = -1, so y = 0
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ];
+        for &hex in &bad {
+            assert!(matches!(decode(hex), Err(ByteArrayError::ConversionError(_))));
+            assert!(RistrettoPublicKey::from_hex(hex).is_err());
+        }
+    }
+
+    #[test]
+    fn secret_to_bytes() {
+        let (k, _) = get_keypair();
+        let mut bytes = k.to_bytes();
+        assert_eq!(&bytes[..], k.as_bytes());
+        assert_eq!(RistrettoSecretKey::from_bytes(&bytes).unwrap(), k);
+        bytes.zeroize();
+        assert_eq!(bytes, [0u8; 32]);
+        assert_eq!(RistrettoSecretKey::from(1).to_bytes()[0], 1);
+    }
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut rng1 = seeded_rng(42);
+        let mut rng2 = seeded_rng(42);
+        for _ in 0..5 {
+            let (k1, p1) = RistrettoPublicKey::random_keypair(&mut rng1);
+            let (k2, p2) = RistrettoPublicKey::random_keypair(&mut rng2);
+            assert_eq!(k1, k2);
+            assert_eq!(p1, p2);
+        }
+        let (k3, _) = RistrettoPublicKey::random_keypair(&mut seeded_rng(43));
+        let (k4, _) = RistrettoPublicKey::random_keypair(&mut seeded_rng(42));
+        assert_ne!(k3, k4);
+    }
+
+    #[test]
+    fn from_bytes_mod_order() {
+        let l = BASEPOINT_ORDER.to_bytes();
+        // l reduces to zero, and l + 1 to one
+        assert_eq!(
+            RistrettoSecretKey::from_bytes_mod_order(l),
+            RistrettoSecretKey::default()
+        );
+        assert!(RistrettoSecretKey::try_from(&l[..]).is_err());
+        let mut l_plus_1 = l;
+        l_plus_1[0] += 1;
+        assert_eq!(
+            RistrettoSecretKey::from_bytes_mod_order(l_plus_1),
+            RistrettoSecretKey::from(1)
+        );
+        assert!(RistrettoSecretKey::try_from(&l_plus_1[..]).is_err());
+        // 2^256 - 1 is reduced rather than rejected
+        let k = RistrettoSecretKey::from_bytes_mod_order([0xff; 32]);
+        assert!(RistrettoSecretKey::try_from(&[0xffu8; 32][..]).is_err());
+        assert_eq!(RistrettoSecretKey::try_from(k.as_bytes()).unwrap(), k);
+        // Canonical values are unchanged
+        let (k, _) = get_keypair();
+        assert_eq!(RistrettoSecretKey::from_bytes_mod_order(k.to_bytes()), k);
+    }
+
+    #[test]
+    fn public_key_arithmetic() {
+        let (_, a) = get_keypair();
+        let (_, b) = get_keypair();
+        let sum = &a + &b;
+        assert_eq!(sum, a.clone() + b.clone());
+        assert_eq!(sum, &a + b.clone());
+        assert_eq!(sum, a.clone() + &b);
+        let diff = &a - &b;
+        assert_eq!(diff, a.clone() - b.clone());
+        assert_eq!(&diff + &b, a);
+        let neg_a = -&a;
+        assert_eq!(neg_a, -a.clone());
+        assert_eq!(&a + &neg_a, RistrettoPublicKey::default());
+        assert_eq!(&b - &a, &b + &neg_a);
+        assert_eq!(-neg_a, a);
+    }
+
+    #[test]
+    fn conditional_select() {
+        let (a, pub_a) = get_keypair();
+        let (b, pub_b) = get_keypair();
+        assert_eq!(RistrettoSecretKey::conditional_select(&a, &b, Choice::from(0)), a);
+        assert_eq!(RistrettoSecretKey::conditional_select(&a, &b, Choice::from(1)), b);
+        assert_eq!(
+            RistrettoPublicKey::conditional_select(&pub_a, &pub_b, Choice::from(0)),
+            pub_a
+        );
+        let selected = RistrettoPublicKey::conditional_select(&pub_a, &pub_b, Choice::from(1));
+        assert_eq!(selected, pub_b);
+        assert_eq!(selected.as_bytes(), pub_b.as_bytes());
+    }
+
+    #[test]
+    fn from_secret_key_with_base() {
+        let (k, p) = get_keypair();
+        let g = RistrettoPublicKey::new_from_pk(RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(RistrettoPublicKey::from_secret_key_with_base(&k, &g), p);
+        let h = RistrettoPublicKey::new_from_pk(RISTRETTO_NUMS_POINTS[0]);
+        let p_h = RistrettoPublicKey::from_secret_key_with_base(&k, &h);
+        assert_ne!(p_h, p);
+        assert_eq!(p_h, &k * &h);
+    }
+
+    #[test]
+    fn public_key_add_assign() {
+        let (_, p1) = get_keypair();
+        let (_, p2) = get_keypair();
+        let mut p = p1.clone();
+        // Cache the compressed encoding before updating the point
+        let _ = p.as_bytes();
+        p += &p2;
+        let sum = &p1 + &p2;
+        assert_eq!(p, sum);
+        assert_eq!(p.as_bytes(), sum.as_bytes());
+        p -= &p2;
+        assert_eq!(p, p1);
+        assert_eq!(p.as_bytes(), p1.as_bytes());
+    }
+
+    #[test]
+    fn from_sum() {
+        let keys = (0..5).map(|_| get_keypair().1).collect::<Vec<_>>();
+        let folded = keys.iter().skip(1).fold(keys[0].clone(), |acc, k| &acc + k);
+        assert_eq!(RistrettoPublicKey::from_sum(&keys), folded);
+        assert_eq!(RistrettoPublicKey::from_sum(&keys[..1]), keys[0]);
+        assert!(RistrettoPublicKey::from_sum(&[]).is_identity());
+        assert_eq!(RistrettoPublicKey::from_sum(&[]), RistrettoPublicKey::default());
+    }
+
+    #[test]
+    fn batch_invert() {
+        let mut rng = rand::thread_rng();
+        let keys = (0..10)
+            .map(|_| RistrettoSecretKey::random(&mut rng))
+            .collect::<Vec<_>>();
+        let mut inverses = keys.clone();
+        RistrettoSecretKey::batch_invert(&mut inverses).unwrap();
+        for (k, inv) in keys.iter().zip(inverses.iter()) {
+            assert_eq!(inv.0, k.0.invert());
+            assert_eq!(k * inv, RistrettoSecretKey::from(1));
+        }
+        let mut empty: [RistrettoSecretKey; 0] = [];
+        assert!(RistrettoSecretKey::batch_invert(&mut empty).is_ok());
+
+        let mut with_zero = keys.clone();
+        with_zero[3] = RistrettoSecretKey::default();
+        assert_eq!(
+            RistrettoSecretKey::batch_invert(&mut with_zero),
+            Err(KeyError::ZeroScalar)
+        );
+        assert_eq!(with_zero[0], keys[0]);
+    }
+
+    #[test]
+    fn public_key_hex_formatting() {
+        let is_lower_hex = |s: &str| s.len() == 64 && s.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'));
+        for _ in 0..10 {
+            let (_, p) = get_keypair();
+            let display = format!("{}", p);
+            assert!(is_lower_hex(&display));
+            assert_eq!(format!("{:x}", p), display);
+            assert_eq!(format!("{:#x}", p), format!("0x{}", display));
+            assert_eq!(format!("{:?}", p), display);
+        }
+    }
 }
@@ -20,11 +20,28 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::convert::TryFrom;
+
+use blake2::Blake2b;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::VartimeMultiscalarMul,
+};
+use digest::Digest;
+use rand::{CryptoRng, RngCore};
+use tari_utilities::{ByteArray, ByteArrayError};
+
 use crate::{
-    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    hash::blake2::Blake256,
+    keys::{PublicKey, SecretKey},
+    ristretto::{challenge::ChallengeBuilder, RistrettoPublicKey, RistrettoSecretKey},
     signatures::SchnorrSignature,
 };
 
+const HEDGED_NONCE_LABEL: &[u8] = b"tari.schnorr.hedged_nonce";
+
 /// # A Schnorr signature implementation on Ristretto
 ///
 /// Find out more about [Schnorr signatures](https://tlu.tarilabs.com/cryptography/digital_signatures/introduction.html).
@@ -97,17 +114,412 @@ use crate::{
 /// let e = Blake256::digest(b"Maskerade");
 /// assert!(sig.verify_challenge(&P, &e));
 /// ```
+///
+/// # Signing and verifying messages
+///
+/// The signer and verifier must construct the challenge in exactly the same way, which is easy to get wrong, e.g. by
+/// hashing the keys in a different order. Unless a protocol requires a particular challenge, prefer
+/// [sign_with_rng](RistrettoSchnorr::sign_with_rng) (or [sign_message](RistrettoSchnorr::sign_message) with your own
+/// nonce) together with [verify_message](RistrettoSchnorr::verify_message). These take the message itself and own
+/// the whole challenge construction, \\( e = H(R || P || m) \\), where _H_ is `Blake256`:
+///
+/// ```edition2018
+/// # use tari_crypto::ristretto::*;
+/// # use tari_crypto::keys::*;
+/// let mut rng = rand::thread_rng();
+/// let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+/// let sig = RistrettoSchnorr::sign_with_rng(&k, b"Going Postal", &mut rng);
+/// assert!(sig.verify_message(&p, b"Going Postal"));
+/// assert!(!sig.verify_message(&p, b"Making Money"));
+/// ```
 pub type RistrettoSchnorr = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey>;
 
+impl RistrettoSchnorr {
+    /// Serialize the signature as 64 bytes: the 32-byte compressed public nonce, _R_, followed by the 32-byte
+    /// little-endian signature scalar, _s_.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.get_public_nonce().as_bytes());
+        bytes[32..].copy_from_slice(self.get_signature().as_bytes());
+        bytes
+    }
+
+    /// Deserialize a signature from the 64-byte layout produced by [to_bytes](RistrettoSchnorr::to_bytes). The public
+    /// nonce must be a canonically encoded Ristretto point, and the signature scalar must be fully reduced mod the
+    /// group order. Otherwise _s_ and _s + l_ would decode to the same signature, allowing anyone to produce a second,
+    /// differently hashed encoding of a valid signature.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, ByteArrayError> {
+        let mut nonce = [0u8; 32];
+        nonce.copy_from_slice(&bytes[..32]);
+        let public_nonce = RistrettoPublicKey::from_canonical_bytes(&nonce)?;
+        let signature = RistrettoSecretKey::try_from(&bytes[32..])?;
+        Ok(RistrettoSchnorr::new(public_nonce, signature))
+    }
+
+    /// Sign `message` with the given `secret` and private `nonce`, using a challenge of
+    /// \\( e = H(R || P || m) \\), where _H_ is `Blake256`. See
+    /// [sign_message_with_digest](RistrettoSchnorr::sign_message_with_digest) to use a different hash function.
+    ///
+    /// The public key, _P_, is derived from `secret` and included in the challenge, so the signature is bound to the
+    /// signer's key. This rules out the related-key attacks that [sign](SchnorrSignature::sign) cannot, since it
+    /// accepts an arbitrary, externally computed challenge.
+    pub fn sign_message(secret: &RistrettoSecretKey, nonce: &RistrettoSecretKey, message: &[u8]) -> Self {
+        RistrettoSchnorr::sign_message_with_digest::<Blake256>(secret, nonce, message)
+    }
+
+    /// Sign `message` with a hedged nonce, \\( r = H(k || a || m) \\), derived from the secret key, the 32 bytes of
+    /// auxiliary randomness `aux_rand`, _a_, and the message, following the approach of BIP-340. The challenge is the
+    /// same as for [sign_message](RistrettoSchnorr::sign_message), so the signature is checked with
+    /// [verify_message](RistrettoSchnorr::verify_message).
+    ///
+    /// A purely deterministic nonce is vulnerable to fault attacks, and a purely random one to a bad RNG. Mixing both
+    /// means the nonce remains secret if either the secret key or `aux_rand` is, so `aux_rand` should be drawn fresh
+    /// from a CSPRNG for every signature, but an all-zero value still yields a safe, deterministic nonce. Use
+    /// [sign_hedged_with_rng](RistrettoSchnorr::sign_hedged_with_rng) to have `aux_rand` drawn from an RNG.
+    pub fn sign_hedged(secret: &RistrettoSecretKey, message: &[u8], aux_rand: &[u8; 32]) -> Self {
+        let hash = Blake2b::new()
+            .chain(HEDGED_NONCE_LABEL)
+            .chain(secret.as_bytes())
+            .chain(aux_rand)
+            .chain(message)
+            .finalize();
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash);
+        let nonce = RistrettoSecretKey::from_uniform_bytes(&bytes);
+        RistrettoSchnorr::sign_message(secret, &nonce, message)
+    }
+
+    /// Sign `message` with a hedged nonce, as [sign_hedged](RistrettoSchnorr::sign_hedged) does, with the auxiliary
+    /// randomness drawn fresh from `rng`. Unlike [sign_with_rng](RistrettoSchnorr::sign_with_rng), the nonce stays
+    /// secret even if `rng` is broken, since it is also derived from the secret key and the message.
+    pub fn sign_hedged_with_rng<R: RngCore + CryptoRng>(
+        secret: &RistrettoSecretKey,
+        message: &[u8],
+        rng: &mut R,
+    ) -> Self {
+        let mut aux_rand = [0u8; 32];
+        rng.fill_bytes(&mut aux_rand);
+        RistrettoSchnorr::sign_hedged(secret, message, &aux_rand)
+    }
+
+    /// Sign `message` with a fresh nonce drawn from `rng`. The challenge is the same as for
+    /// [sign_message](RistrettoSchnorr::sign_message), so the signature is checked with
+    /// [verify_message](RistrettoSchnorr::verify_message).
+    ///
+    /// Reusing a nonce, or using one that can be predicted, reveals the secret key. The `CryptoRng` bound ensures that
+    /// only RNGs that are marked as cryptographically secure can be used:
+    ///
+    /// ```edition2018
+    /// # use tari_crypto::{keys::PublicKey, ristretto::{RistrettoPublicKey, RistrettoSchnorr}};
+    /// let mut rng = rand::thread_rng();
+    /// let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+    /// let sig = RistrettoSchnorr::sign_with_rng(&k, b"Hello", &mut rng);
+    /// assert!(sig.verify_message(&p, b"Hello"));
+    /// ```
+    ///
+    /// An RNG without the marker is rejected at compile time:
+    ///
+    /// ```compile_fail,edition2018
+    /// # use rand::RngCore;
+    /// # use tari_crypto::{keys::PublicKey, ristretto::{RistrettoPublicKey, RistrettoSchnorr}};
+    /// struct Counter(u64);
+    ///
+    /// impl RngCore for Counter {
+    ///     fn next_u32(&mut self) -> u32 {
+    ///         self.next_u64() as u32
+    ///     }
+    ///
+    ///     fn next_u64(&mut self) -> u64 {
+    ///         self.0 += 1;
+    ///         self.0
+    ///     }
+    ///
+    ///     fn fill_bytes(&mut self, dest: &mut [u8]) {
+    ///         dest.iter_mut().for_each(|b| *b = self.next_u32() as u8)
+    ///     }
+    ///
+    ///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+    ///         self.fill_bytes(dest);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let (k, _) = RistrettoPublicKey::random_keypair(&mut rand::thread_rng());
+    /// let sig = RistrettoSchnorr::sign_with_rng(&k, b"Hello", &mut Counter(0));
+    /// ```
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(secret: &RistrettoSecretKey, message: &[u8], rng: &mut R) -> Self {
+        let nonce = RistrettoSecretKey::random_non_zero(rng);
+        RistrettoSchnorr::sign_message(secret, &nonce, message)
+    }
+
+    /// Returns true if this signature was produced by [sign_message](RistrettoSchnorr::sign_message) for `message`
+    /// and the secret key corresponding to `public_key`.
+    pub fn verify_message(&self, public_key: &RistrettoPublicKey, message: &[u8]) -> bool {
+        self.verify_message_with_digest::<Blake256>(public_key, message)
+    }
+
+    /// Sign `message` using a challenge of \\( e = H(R || P || m) \\) for the given hash function, _H_. The digest is
+    /// reduced to a scalar with [RistrettoSecretKey::from_hash_bytes], so digests of any output size (e.g. 32-byte
+    /// `Blake256` or 64-byte `Blake2b` or `Sha512`) can be used.
+    pub fn sign_message_with_digest<D: Digest>(
+        secret: &RistrettoSecretKey,
+        nonce: &RistrettoSecretKey,
+        message: &[u8],
+    ) -> Self {
+        let public_key = RistrettoPublicKey::from_secret_key(secret);
+        let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+        let e = RistrettoSchnorr::message_challenge::<D>(&public_nonce, &public_key, message);
+        let s = nonce + &(&e * secret);
+        RistrettoSchnorr::new(public_nonce, s)
+    }
+
+    /// Returns true if this signature was produced by
+    /// [sign_message_with_digest](RistrettoSchnorr::sign_message_with_digest) using the same hash function, for
+    /// `message` and the secret key corresponding to `public_key`.
+    pub fn verify_message_with_digest<D: Digest>(&self, public_key: &RistrettoPublicKey, message: &[u8]) -> bool {
+        let e = RistrettoSchnorr::message_challenge::<D>(self.get_public_nonce(), public_key, message);
+        self.verify_vartime(public_key, &e)
+    }
+
+    /// Returns true if this signature is valid for `public_key` and the challenge scalar `challenge`. The result is
+    /// always the same as that of [verify](SchnorrSignature::verify), including the rejection of identity keys and
+    /// nonces, but \\( s.G - e.P \\) is calculated with a single variable-time multiscalar multiplication.
+    ///
+    /// Variable-time arithmetic is safe here because every input to verification, i.e. the signature, the public key
+    /// and the challenge, is public. Timing can only leak information about secret values, and there are none.
+    /// Signing always uses constant-time arithmetic, since the secret key and nonce are involved.
+    pub fn verify_vartime(&self, public_key: &RistrettoPublicKey, challenge: &RistrettoSecretKey) -> bool {
+        if public_key.is_identity() || self.get_public_nonce().is_identity() {
+            return false;
+        }
+        let s = self.get_signature().0;
+        let e = challenge.0;
+        let p = RistrettoPoint::from(public_key);
+        let r = RistrettoPoint::vartime_multiscalar_mul(&[s, -e], &[RISTRETTO_BASEPOINT_POINT, p]);
+        r == RistrettoPoint::from(self.get_public_nonce())
+    }
+
+    /// Sign `message` exactly as [sign_message](RistrettoSchnorr::sign_message) does, but also return the challenge
+    /// scalar, \\( e = H(R || P || m) \\), that was signed. This is useful for logging and comparing challenges across
+    /// implementations. As with `sign_message`, the public key in the challenge is derived from `secret`.
+    pub fn sign_returning_challenge(
+        secret: &RistrettoSecretKey,
+        nonce: &RistrettoSecretKey,
+        message: &[u8],
+    ) -> (Self, RistrettoSecretKey) {
+        let public_key = RistrettoPublicKey::from_secret_key(secret);
+        let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+        let e = RistrettoSchnorr::message_challenge::<Blake256>(&public_nonce, &public_key, message);
+        let s = nonce + &(&e * secret);
+        (RistrettoSchnorr::new(public_nonce, s), e)
+    }
+
+    /// Verify the signature exactly as [verify_message](RistrettoSchnorr::verify_message) does, and also return the
+    /// challenge scalar that the signature was checked against.
+    pub fn verify_returning_challenge(
+        &self,
+        public_key: &RistrettoPublicKey,
+        message: &[u8],
+    ) -> (bool, RistrettoSecretKey) {
+        let e = RistrettoSchnorr::message_challenge::<Blake256>(self.get_public_nonce(), public_key, message);
+        (self.verify(public_key, &e), e)
+    }
+
+    /// Sign a challenge that the caller has already hashed to 64 bytes, e.g. with SHA-512. The digest is reduced to
+    /// the challenge scalar with [RistrettoSecretKey::from_uniform_bytes], which avoids the bias of reducing a
+    /// 32-byte value that [sign](SchnorrSignature::sign) accepts. As with [sign](SchnorrSignature::sign), the digest
+    /// is used as is, so it is up to the caller to commit to the public nonce and public key in it.
+    pub fn sign_prehashed_wide(secret: &RistrettoSecretKey, nonce: &RistrettoSecretKey, digest: &[u8; 64]) -> Self {
+        let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+        let e = RistrettoSecretKey::from_uniform_bytes(digest);
+        let s = nonce + &(&e * secret);
+        RistrettoSchnorr::new(public_nonce, s)
+    }
+
+    /// Returns true if this signature was produced by [sign_prehashed_wide](RistrettoSchnorr::sign_prehashed_wide)
+    /// over `digest` with the secret key corresponding to `public_key`.
+    pub fn verify_prehashed_wide(&self, public_key: &RistrettoPublicKey, digest: &[u8; 64]) -> bool {
+        let e = RistrettoSecretKey::from_uniform_bytes(digest);
+        self.verify(public_key, &e)
+    }
+
+    /// Returns true if this signature is valid for `public_key` and a challenge given as raw bytes, e.g. a hash digest.
+    /// The bytes are reduced to a scalar with [RistrettoSecretKey::from_hash_bytes]. For a 32-byte challenge this is
+    /// the same reduction that [sign](SchnorrSignature::sign) applies, so the result agrees with
+    /// [verify_challenge](SchnorrSignature::verify_challenge). Unlike `verify_challenge`, challenges of other lengths
+    /// are accepted too, and a 64-byte challenge is reduced as in
+    /// [sign_prehashed_wide](RistrettoSchnorr::sign_prehashed_wide).
+    pub fn verify_challenge_bytes(&self, public_key: &RistrettoPublicKey, challenge_bytes: &[u8]) -> bool {
+        let e = RistrettoSecretKey::from_hash_bytes(challenge_bytes);
+        self.verify(public_key, &e)
+    }
+
+    /// Verify a set of signatures, each made over the **same** challenge `e` by a different key, by summing them and
+    /// checking the aggregate once:
+    /// $$
+    ///   (\sum s_i).G = \sum R_i + e \sum P_i
+    /// $$
+    /// This only works if every part was signed with the same challenge, typically one that commits to all of the
+    /// nonces and public keys involved. Individual signatures over different challenges will not verify in aggregate
+    /// even if each of them is valid, and a valid aggregate says nothing about the validity of any individual part.
+    ///
+    /// Returns false if `parts` is empty.
+    pub fn verify_aggregate(
+        parts: &[(&RistrettoSchnorr, &RistrettoPublicKey)],
+        challenge: &RistrettoSecretKey,
+    ) -> bool {
+        if parts.is_empty() {
+            return false;
+        }
+        let mut public_nonce = RistrettoPublicKey::default();
+        let mut signature = RistrettoSecretKey::default();
+        let mut public_key = RistrettoPublicKey::default();
+        for (sig, p) in parts {
+            public_nonce = &public_nonce + sig.get_public_nonce();
+            signature = &signature + sig.get_signature();
+            public_key = &public_key + *p;
+        }
+        RistrettoSchnorr::new(public_nonce, signature).verify(&public_key, challenge)
+    }
+
+    /// Verify several signatures made with [sign_message](RistrettoSchnorr::sign_message) over the same message, each
+    /// from a different key, returning the result for each entry in order.
+    ///
+    /// Each entry is checked against its own challenge, \\( e = H(R || P || m) \\), exactly as
+    /// [verify_message](RistrettoSchnorr::verify_message) does. The challenge absorbs the nonce and public key before
+    /// the message, so there is no hash state that could be shared between entries; a challenge over the message
+    /// alone would let anyone forge a signature for any key.
+    pub fn verify_same_message_batch(
+        message: &[u8],
+        entries: &[(&RistrettoSchnorr, &RistrettoPublicKey)],
+    ) -> Vec<bool> {
+        entries.iter().map(|(sig, p)| sig.verify_message(*p, message)).collect()
+    }
+
+    /// Calculate the public key implied by this signature and `challenge`, \\( P = e^{-1}(s.G - R) \\). If the
+    /// signature is valid for some key and this challenge, the result is that key. Otherwise it is an unrelated point,
+    /// so the recovered key must be checked (e.g. against a known address) before it is trusted.
+    ///
+    /// The challenge bytes are reduced to a scalar as in [verify_challenge](SchnorrSignature::verify_challenge).
+    /// Returns `None` if the challenge is not 32 bytes long, or is zero and so cannot be inverted. Note that for
+    /// challenges which commit to the public key, such as those of [sign_message](RistrettoSchnorr::sign_message),
+    /// the key must already be known to calculate the challenge in the first place.
+    pub fn recover_public_key(&self, challenge: &[u8]) -> Option<RistrettoPublicKey> {
+        let e = RistrettoSecretKey::from_bytes(challenge).ok()?;
+        if e.0 == Scalar::zero() {
+            return None;
+        }
+        let e_inv = RistrettoSecretKey(e.0.invert());
+        let s_g = RistrettoPublicKey::from_secret_key(self.get_signature());
+        Some(&(&s_g - self.get_public_nonce()) * &e_inv)
+    }
+
+    /// Calculates the message challenge, \\( e = H(R || P || m) \\), reduced to a scalar
+    pub(crate) fn message_challenge<D: Digest>(
+        public_nonce: &RistrettoPublicKey,
+        public_key: &RistrettoPublicKey,
+        message: &[u8],
+    ) -> RistrettoSecretKey {
+        let hash = D::new()
+            .chain(public_nonce.as_bytes())
+            .chain(public_key.as_bytes())
+            .chain(message)
+            .finalize();
+        RistrettoSecretKey::from_hash_bytes(&hash)
+    }
+
+    /// Sign a structured message, made up of several `fields`, with the given `secret` and private `nonce`. The
+    /// challenge is built with a [ChallengeBuilder] from the public nonce, the public key of `secret` and each of the
+    /// fields in turn, with every field prefixed by its length, so different ways of splitting the same bytes into
+    /// fields, e.g. `["ab", "c"]` and `["a", "bc"]`, never produce the same challenge.
+    ///
+    /// The signature is checked with [verify_structured](RistrettoSchnorr::verify_structured).
+    pub fn sign_structured(secret: &RistrettoSecretKey, nonce: &RistrettoSecretKey, fields: &[&[u8]]) -> Self {
+        let public_key = RistrettoPublicKey::from_secret_key(secret);
+        let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+        let e = RistrettoSchnorr::structured_challenge(&public_nonce, &public_key, fields).finalize_challenge();
+        let s = nonce + &(&e * secret);
+        RistrettoSchnorr::new(public_nonce, s)
+    }
+
+    /// Returns true if this signature was produced by [sign_structured](RistrettoSchnorr::sign_structured) for the
+    /// given `fields` and the secret key corresponding to `public_key`.
+    pub fn verify_structured(&self, public_key: &RistrettoPublicKey, fields: &[&[u8]]) -> bool {
+        let e =
+            RistrettoSchnorr::structured_challenge(self.get_public_nonce(), public_key, fields).finalize_challenge();
+        self.verify(public_key, &e)
+    }
+
+    fn structured_challenge(
+        public_nonce: &RistrettoPublicKey,
+        public_key: &RistrettoPublicKey,
+        fields: &[&[u8]],
+    ) -> ChallengeBuilder {
+        let mut builder = ChallengeBuilder::new();
+        builder.update(public_nonce.as_bytes());
+        builder.update(public_key.as_bytes());
+        for field in fields {
+            builder.update_field(field);
+        }
+        builder
+    }
+
+    /// Sign a message that is streamed into the challenge by `write_message`, rather than held in memory in one go.
+    /// The public nonce and public key are absorbed into a [ChallengeBuilder::for_message] builder before
+    /// `write_message` is called, so the challenge is \\( e = H(R || P || m) \\), exactly as for
+    /// [sign_message](RistrettoSchnorr::sign_message), and the signature verifies with
+    /// [verify_message](RistrettoSchnorr::verify_message).
+    pub fn sign_with_challenge_builder<F: FnOnce(&mut ChallengeBuilder)>(
+        secret: &RistrettoSecretKey,
+        nonce: &RistrettoSecretKey,
+        write_message: F,
+    ) -> Self {
+        let public_nonce = RistrettoPublicKey::from_secret_key(nonce);
+        let public_key = RistrettoPublicKey::from_secret_key(secret);
+        let mut builder = ChallengeBuilder::for_message(&public_nonce, &public_key);
+        write_message(&mut builder);
+        let e = builder.finalize_challenge();
+        let s = nonce + &(&e * secret);
+        RistrettoSchnorr::new(public_nonce, s)
+    }
+
+    /// Returns true if this signature is valid for `public_key` and the message streamed in by `write_message`. This
+    /// is the streaming counterpart of [verify_message](RistrettoSchnorr::verify_message), and accepts signatures
+    /// made with either [sign_message](RistrettoSchnorr::sign_message) or
+    /// [sign_with_challenge_builder](RistrettoSchnorr::sign_with_challenge_builder).
+    pub fn verify_with_challenge_builder<F: FnOnce(&mut ChallengeBuilder)>(
+        &self,
+        public_key: &RistrettoPublicKey,
+        write_message: F,
+    ) -> bool {
+        let mut builder = ChallengeBuilder::for_message(self.get_public_nonce(), public_key);
+        write_message(&mut builder);
+        self.verify(public_key, &builder.finalize_challenge())
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use blake2::Blake2b;
     use digest::Digest;
-    use tari_utilities::{hex::from_hex, ByteArray};
+    use rand::RngCore;
+    use tari_utilities::{
+        hex::{from_hex, to_hex, Hex},
+        message_format::MessageFormat,
+        ByteArray,
+    };
 
     use crate::{
         common::Blake256,
         keys::{PublicKey, SecretKey},
-        ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+        ristretto::{
+            challenge::ChallengeBuilder,
+            test_common::add_group_order,
+            RistrettoPublicKey,
+            RistrettoSchnorr,
+            RistrettoSecretKey,
+        },
+        signatures::SchnorrSignatureError,
     };
 
     #[test]
@@ -172,6 +584,50 @@ mod test {
         assert!(s_agg.verify_challenge(&(P1 + P2), &e));
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn verify_aggregate() {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<_> = (0..3).map(|_| RistrettoPublicKey::random_keypair(&mut rng)).collect();
+        let nonces: Vec<_> = (0..3).map(|_| RistrettoPublicKey::random_keypair(&mut rng)).collect();
+        // The challenge commits to every nonce and public key
+        let e = keys
+            .iter()
+            .chain(nonces.iter())
+            .fold(Blake256::new(), |h, (_, p)| h.chain(p.as_bytes()))
+            .chain(b"Moving Pictures")
+            .finalize();
+        let e = RistrettoSecretKey::from_bytes(&e).unwrap();
+        let sigs: Vec<RistrettoSchnorr> = keys
+            .iter()
+            .zip(nonces.iter())
+            .map(|((k, _), (r, _))| RistrettoSchnorr::sign(k.clone(), r.clone(), e.as_bytes()).unwrap())
+            .collect();
+        // Two parties, as in test_signature_addition
+        let two = [(&sigs[0], &keys[0].1), (&sigs[1], &keys[1].1)];
+        assert!(RistrettoSchnorr::verify_aggregate(&two, &e));
+        assert_eq!(
+            RistrettoSchnorr::verify_aggregate(&two, &e),
+            (&sigs[0] + &sigs[1]).verify(&(&keys[0].1 + &keys[1].1), &e)
+        );
+        // Three parties
+        let three = [(&sigs[0], &keys[0].1), (&sigs[1], &keys[1].1), (&sigs[2], &keys[2].1)];
+        assert!(RistrettoSchnorr::verify_aggregate(&three, &e));
+        // A missing key, a mismatched key or a different challenge all fail
+        let missing = [(&sigs[0], &keys[0].1), (&sigs[1], &keys[1].1), (&sigs[2], &keys[1].1)];
+        assert!(!RistrettoSchnorr::verify_aggregate(&missing, &e));
+        assert!(!RistrettoSchnorr::verify_aggregate(
+            &three,
+            &RistrettoSecretKey::from(1)
+        ));
+        assert!(!RistrettoSchnorr::verify_aggregate(&[], &e));
+        // Signatures with different challenges do not aggregate
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let other = RistrettoSchnorr::sign_message(&keys[2].0, &r, b"Moving Pictures");
+        let mixed = [(&sigs[0], &keys[0].1), (&sigs[1], &keys[1].1), (&other, &keys[2].1)];
+        assert!(!RistrettoSchnorr::verify_aggregate(&mixed, &e));
+    }
+
     /// Ristretto scalars have a max value 2^255. This test checks that hashed messages above this value can still be
     /// signed as a result of applying modulo arithmetic on the challenge value
     #[test]
@@ -182,4 +638,455 @@ mod test {
         let r = RistrettoSecretKey::random(&mut rng);
         assert!(RistrettoSchnorr::sign(k, r, &m).is_ok());
     }
+
+    /// Challenges reduced with `from_hash_bytes` match the reduction that the signer applies internally
+    #[test]
+    fn challenge_reduction_matches_signer() {
+        let mut rng = rand::thread_rng();
+        let m = from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+        let k = RistrettoSecretKey::random(&mut rng);
+        let r = RistrettoSecretKey::random(&mut rng);
+        let e = RistrettoSecretKey::from_hash_bytes(&m);
+        let s = &r + &(&e * &k);
+        let p = RistrettoPublicKey::from_secret_key(&k);
+        let sig = RistrettoSchnorr::sign(k, r, &m).unwrap();
+        assert_eq!(sig.get_signature(), &s);
+        assert!(sig.verify(&p, &e));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn sign_with_streamed_challenge() {
+        let mut rng = rand::thread_rng();
+        let (k, P) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, R) = RistrettoPublicKey::random_keypair(&mut rng);
+        let stream = |builder: &mut ChallengeBuilder| {
+            for chunk in &[&b"Reaper "[..], &b"Man"[..]] {
+                builder.update(chunk);
+            }
+        };
+        let sig = RistrettoSchnorr::sign_with_challenge_builder(&k, &r, stream);
+        assert_eq!(sig.get_public_nonce(), &R);
+        assert!(sig.verify_message(&P, b"Reaper Man"));
+        assert!(sig.verify_with_challenge_builder(&P, stream));
+        assert_eq!(sig, RistrettoSchnorr::sign_message(&k, &r, b"Reaper Man"));
+        assert!(!sig.verify_with_challenge_builder(&P, |b| b.update(b"Reaper Woman")));
+    }
+
+    #[test]
+    fn identity_nonce_is_rejected() {
+        let mut rng = rand::thread_rng();
+        // With e = 1 and P = s.G, the verification equation s.G = R + e.P holds for R = 0
+        let s = RistrettoSecretKey::random(&mut rng);
+        let p = RistrettoPublicKey::from_secret_key(&s);
+        let mut e = [0u8; 32];
+        e[0] = 1;
+        let sig = RistrettoSchnorr::new(RistrettoPublicKey::default(), s);
+        assert!(sig.get_public_nonce().is_identity());
+        assert!(!sig.verify_challenge(&p, &e));
+        assert!(!sig.verify(&p, &RistrettoSecretKey::from(1u64)));
+    }
+
+    #[test]
+    fn try_verify_challenge_reports_failures() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let e = Blake256::digest(b"Thief of Time");
+        let sig = RistrettoSchnorr::sign(k.clone(), r, &e).unwrap();
+        assert_eq!(sig.try_verify_challenge(&p, &e), Ok(()));
+        assert!(sig.verify_challenge(&p, &e));
+        // Wrong challenge
+        let wrong = Blake256::digest(b"Night Watch");
+        assert_eq!(
+            sig.try_verify_challenge(&p, &wrong),
+            Err(SchnorrSignatureError::EquationMismatch)
+        );
+        assert!(!sig.verify_challenge(&p, &wrong));
+        // Malformed challenge
+        assert_eq!(
+            sig.try_verify_challenge(&p, &e[..31]),
+            Err(SchnorrSignatureError::InvalidChallenge)
+        );
+        // Identity public key
+        assert_eq!(
+            sig.try_verify_challenge(&RistrettoPublicKey::default(), &e),
+            Err(SchnorrSignatureError::InvalidPublicKey)
+        );
+        // Identity nonce
+        let sig = RistrettoSchnorr::new(RistrettoPublicKey::default(), k);
+        assert_eq!(
+            sig.try_verify(&p, &RistrettoSecretKey::from(1u64)),
+            Err(SchnorrSignatureError::NonceIsIdentity)
+        );
+    }
+
+    #[test]
+    fn sign_returning_challenge() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (sig, e) = RistrettoSchnorr::sign_returning_challenge(&k, &r, b"Jingo");
+        assert_eq!(sig, RistrettoSchnorr::sign_message(&k, &r, b"Jingo"));
+        // Feeding the challenge back into the raw signing path reproduces the signature
+        let raw = RistrettoSchnorr::sign(k, r, e.as_bytes()).unwrap();
+        assert_eq!(sig, raw);
+        let (valid, e2) = sig.verify_returning_challenge(&p, b"Jingo");
+        assert!(valid);
+        assert_eq!(e, e2);
+        let (valid, e3) = sig.verify_returning_challenge(&p, b"Jingo!");
+        assert!(!valid);
+        assert_ne!(e, e3);
+    }
+
+    #[test]
+    fn signature_bytes_round_trip() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"Soul Music");
+        let bytes = sig.to_bytes();
+        assert_eq!(&bytes[..32], sig.get_public_nonce().as_bytes());
+        assert_eq!(&bytes[32..], sig.get_signature().as_bytes());
+        let sig2 = RistrettoSchnorr::from_bytes(&bytes).unwrap();
+        assert_eq!(sig, sig2);
+        assert!(sig2.verify_message(&p, b"Soul Music"));
+        // Corrupt the public nonce with a non-canonical encoding
+        let mut bad = bytes;
+        bad[..32].copy_from_slice(&[0xff; 32]);
+        assert!(RistrettoSchnorr::from_bytes(&bad).is_err());
+    }
+
+    #[test]
+    fn non_canonical_signature_scalar_is_rejected() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"Malleable");
+        let mut bytes = sig.to_bytes();
+        assert!(RistrettoSchnorr::from_bytes(&bytes)
+            .unwrap()
+            .verify_message(&p, b"Malleable"));
+        let s_plus_l = add_group_order(&bytes[32..]);
+        // s + l reduces to s, so a lenient decoder would accept it as the same signature
+        assert_eq!(RistrettoSecretKey::from_bytes(&s_plus_l).unwrap(), *sig.get_signature());
+        bytes[32..].copy_from_slice(&s_plus_l);
+        assert!(RistrettoSchnorr::from_bytes(&bytes).is_err());
+        // The serde encodings are strict too
+        let json = format!(
+            r#"{{"public_nonce":"{}","signature":"{}"}}"#,
+            sig.get_public_nonce().to_hex(),
+            to_hex(&s_plus_l)
+        );
+        assert!(RistrettoSchnorr::from_json(&json).is_err());
+        let bin = bincode::serialize(&bytes.to_vec()).unwrap();
+        assert!(bincode::deserialize::<RistrettoSchnorr>(&bin).is_err());
+        assert!(RistrettoSchnorr::from_json(&sig.to_json().unwrap()).is_ok());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn message_challenge_matches_manual_construction() {
+        let mut rng = rand::thread_rng();
+        let (k, P) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, R) = RistrettoPublicKey::random_keypair(&mut rng);
+        let msg = b"Thud!";
+        let sig = RistrettoSchnorr::sign_message(&k, &r, msg);
+        let manual = Blake256::new()
+            .chain(R.as_bytes())
+            .chain(P.as_bytes())
+            .chain(msg)
+            .finalize();
+        let e = RistrettoSchnorr::message_challenge::<Blake256>(&R, &P, msg);
+        assert_eq!(e.as_bytes(), &manual[..]);
+        assert!(sig.verify_challenge(&P, &manual));
+        assert!(sig.verify_message(&P, msg));
+        // Hashing the keys in the other order gives a different challenge, which the signature does not verify against
+        let swapped = Blake256::new()
+            .chain(P.as_bytes())
+            .chain(R.as_bytes())
+            .chain(msg)
+            .finalize();
+        assert!(!sig.verify_challenge(&P, &swapped));
+    }
+
+    #[test]
+    fn sign_structured() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let r = RistrettoSecretKey::random(&mut rng);
+        let fields: &[&[u8]] = &[b"ab", b"c"];
+        let resplit: &[&[u8]] = &[b"a", b"bc"];
+        let sig = RistrettoSchnorr::sign_structured(&k, &r, fields);
+        assert!(sig.verify_structured(&p, fields));
+        assert!(!sig.verify_structured(&p, resplit));
+        let joined: &[&[u8]] = &[b"abc"];
+        assert!(!sig.verify_structured(&p, joined));
+        // The same nonce over the re-split fields gives a different signature
+        let sig2 = RistrettoSchnorr::sign_structured(&k, &r, resplit);
+        assert_ne!(sig.get_signature(), sig2.get_signature());
+        assert!(sig2.verify_structured(&p, resplit));
+    }
+
+    #[test]
+    fn sign_with_rng() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig1 = RistrettoSchnorr::sign_with_rng(&k, b"Fresh nonces", &mut rng);
+        let sig2 = RistrettoSchnorr::sign_with_rng(&k, b"Fresh nonces", &mut rng);
+        assert!(sig1.verify_message(&p, b"Fresh nonces"));
+        assert!(sig2.verify_message(&p, b"Fresh nonces"));
+        assert!(!sig1.verify_message(&p, b"Stale nonces"));
+        assert_ne!(sig1.get_public_nonce(), sig2.get_public_nonce());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn sign_prehashed_wide() {
+        let digest = [0xffu8; 64];
+        // (2^512 - 1) mod l, rather than the (2^256 - 1) mod l of the 32-byte path
+        let e = RistrettoSecretKey::from_uniform_bytes(&digest);
+        assert_eq!(
+            e.to_hex(),
+            "000f9c44e31106a447938568a71b0ed065bef517d273ecce3d9a307c1b419903"
+        );
+        assert_eq!(
+            RistrettoSecretKey::from_bytes(&digest[..32]).unwrap().to_hex(),
+            "1c95988d7431ecd670cf7d73f45befc6feffffffffffffffffffffffffffff0f"
+        );
+        let mut rng = rand::thread_rng();
+        let (k, P) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_prehashed_wide(&k, &r, &digest);
+        assert!(sig.verify(&P, &e));
+        assert!(sig.verify_prehashed_wide(&P, &digest));
+        let mut other = digest;
+        other[63] = 0xfe;
+        assert!(!sig.verify_prehashed_wide(&P, &other));
+    }
+
+    #[test]
+    fn verify_vartime_matches_verify() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, other) = RistrettoPublicKey::random_keypair(&mut rng);
+        for _ in 0..20 {
+            let r = RistrettoSecretKey::random(&mut rng);
+            let e = RistrettoSecretKey::random(&mut rng);
+            let sig = RistrettoSchnorr::sign(k.clone(), r, e.as_bytes()).unwrap();
+            let wrong_e = &e + &RistrettoSecretKey::from(1u64);
+            for (key, challenge) in &[(&p, &e), (&p, &wrong_e), (&other, &e)] {
+                assert_eq!(sig.verify_vartime(key, challenge), sig.verify(key, challenge));
+            }
+            assert!(sig.verify_vartime(&p, &e));
+        }
+        // The identity checks are the same too
+        let identity = RistrettoPublicKey::default();
+        let zero = RistrettoSecretKey::default();
+        let sig = RistrettoSchnorr::sign(zero.clone(), k.clone(), zero.as_bytes()).unwrap();
+        assert!(!sig.verify_vartime(&identity, &zero));
+        assert!(!sig.verify(&identity, &zero));
+        let sig = RistrettoSchnorr::new(identity, zero.clone());
+        assert!(!sig.verify_vartime(&p, &zero));
+        assert!(!sig.verify(&p, &zero));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn verify_challenge_bytes() {
+        let mut rng = rand::thread_rng();
+        let (k, P) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        // A 32-byte digest with the top bit set, so that the challenge has to be reduced
+        let mut challenge = Blake256::digest(b"Going Postal");
+        challenge[31] |= 0x80;
+        let sig = RistrettoSchnorr::sign(k.clone(), r.clone(), &challenge).unwrap();
+        assert!(sig.verify_challenge(&P, &challenge));
+        assert!(sig.verify_challenge_bytes(&P, &challenge));
+        let e = RistrettoSecretKey::from_bytes(&challenge).unwrap();
+        assert!(sig.verify(&P, &e));
+        let other = Blake256::digest(b"Making Money");
+        assert_eq!(sig.verify_challenge_bytes(&P, &other), sig.verify_challenge(&P, &other));
+        assert!(!sig.verify_challenge_bytes(&P, &other));
+        // 64-byte challenges are reduced the same way as for sign_prehashed_wide
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&Blake2b::digest(b"Raising Steam"));
+        let sig = RistrettoSchnorr::sign_prehashed_wide(&k, &r, &wide);
+        assert!(sig.verify_challenge_bytes(&P, &wide));
+        assert!(!sig.verify_challenge(&P, &wide));
+    }
+
+    #[test]
+    fn recover_public_key() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let r = RistrettoSecretKey::random(&mut rng);
+        let e = Blake256::digest(b"Who signed this?");
+        let sig = RistrettoSchnorr::sign(k.clone(), r.clone(), &e).unwrap();
+        assert_eq!(sig.recover_public_key(&e), Some(p.clone()));
+        // A different challenge recovers a different key
+        let e2 = Blake256::digest(b"Someone else");
+        assert_ne!(sig.recover_public_key(&e2), Some(p.clone()));
+        // Message signatures recover the key from the challenge they were made with
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"Who signed this?");
+        let e = RistrettoSchnorr::message_challenge::<Blake256>(sig.get_public_nonce(), &p, b"Who signed this?");
+        assert_eq!(sig.recover_public_key(e.as_bytes()), Some(p));
+        // Zero cannot be inverted, and the challenge must be 32 bytes
+        assert_eq!(sig.recover_public_key(&[0u8; 32]), None);
+        assert_eq!(sig.recover_public_key(&[1u8; 31]), None);
+    }
+
+    #[test]
+    fn sign_message_with_different_digests() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let msg = b"Wyrd Sisters";
+        let sig = RistrettoSchnorr::sign_message(&k, &r, msg);
+        assert!(sig.verify_message(&p, msg));
+        assert!(sig.verify_message_with_digest::<Blake256>(&p, msg));
+        assert!(!sig.verify_message(&p, b"Witches Abroad"));
+        // A 64-byte digest
+        let sig = RistrettoSchnorr::sign_message_with_digest::<Blake2b>(&k, &r, msg);
+        assert!(sig.verify_message_with_digest::<Blake2b>(&p, msg));
+        assert!(!sig.verify_message(&p, msg));
+        let e = Blake2b::new()
+            .chain(sig.get_public_nonce().as_bytes())
+            .chain(p.as_bytes())
+            .chain(msg)
+            .finalize();
+        assert!(sig.verify(&p, &RistrettoSecretKey::from_hash_bytes(&e)));
+    }
+
+    #[test]
+    fn message_signature_is_bound_to_the_key() {
+        let mut rng = rand::thread_rng();
+        let (k1, p1) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, p2) = RistrettoPublicKey::random_keypair(&mut rng);
+        let r = RistrettoSecretKey::random(&mut rng);
+        let msg = b"Thief of Time";
+        let sig = RistrettoSchnorr::sign_message(&k1, &r, msg);
+        assert!(sig.verify_message(&p1, msg));
+        assert!(!sig.verify_message(&p2, msg));
+        // The challenge commits to P, so the signature does not verify under the challenge for a different key either
+        let e = Blake256::new()
+            .chain(sig.get_public_nonce().as_bytes())
+            .chain(p2.as_bytes())
+            .chain(msg)
+            .finalize();
+        assert!(!sig.verify_challenge(&p2, &e));
+        assert!(!sig.verify_challenge(&p1, &e));
+    }
+
+    #[test]
+    fn sign_hedged() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let msg = b"Night Watch";
+        let mut aux1 = [0u8; 32];
+        let mut aux2 = [0u8; 32];
+        rng.fill_bytes(&mut aux1);
+        rng.fill_bytes(&mut aux2);
+        let sig1 = RistrettoSchnorr::sign_hedged(&k, msg, &aux1);
+        let sig2 = RistrettoSchnorr::sign_hedged(&k, msg, &aux2);
+        assert_ne!(sig1.get_public_nonce(), sig2.get_public_nonce());
+        assert!(sig1.verify_message(&p, msg));
+        assert!(sig2.verify_message(&p, msg));
+        // The same inputs always give the same signature, including with no auxiliary randomness
+        assert_eq!(RistrettoSchnorr::sign_hedged(&k, msg, &aux1), sig1);
+        let sig3 = RistrettoSchnorr::sign_hedged(&k, msg, &[0u8; 32]);
+        assert!(sig3.verify_message(&p, msg));
+        assert_eq!(RistrettoSchnorr::sign_hedged(&k, msg, &[0u8; 32]), sig3);
+        let sig4 = RistrettoSchnorr::sign_hedged_with_rng(&k, msg, &mut rng);
+        let sig5 = RistrettoSchnorr::sign_hedged_with_rng(&k, msg, &mut rng);
+        assert!(sig4.verify_message(&p, msg));
+        assert_ne!(sig4.get_public_nonce(), sig5.get_public_nonce());
+    }
+
+    #[test]
+    fn sign_with_nonce_point() {
+        let mut rng = rand::thread_rng();
+        let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, nonce_point) = RistrettoPublicKey::random_keypair(&mut rng);
+        let e = Blake256::digest(b"Going Postal");
+        let sig = RistrettoSchnorr::sign(k.clone(), r.clone(), &e).unwrap();
+        let sig2 = RistrettoSchnorr::sign_with_nonce_point(k.clone(), r.clone(), nonce_point, &e).unwrap();
+        assert_eq!(sig, sig2);
+        assert!(sig2.verify_challenge(&p, &e));
+        let nonce_point = RistrettoPublicKey::from_secret_key(&r);
+        assert_eq!(
+            RistrettoSchnorr::sign_with_nonce_point(k, r, nonce_point, &[0u8; 31]),
+            Err(SchnorrSignatureError::InvalidChallenge)
+        );
+    }
+
+    #[test]
+    fn verify_same_message_batch() {
+        let mut rng = rand::thread_rng();
+        let msg = b"Men at Arms";
+        let signers = (0..4)
+            .map(|_| {
+                let (k, p) = RistrettoPublicKey::random_keypair(&mut rng);
+                let r = RistrettoSecretKey::random(&mut rng);
+                (RistrettoSchnorr::sign_message(&k, &r, msg), p)
+            })
+            .collect::<Vec<_>>();
+        let (_, wrong_key) = RistrettoPublicKey::random_keypair(&mut rng);
+        let entries = signers
+            .iter()
+            .enumerate()
+            .map(|(i, (sig, p))| if i == 2 { (sig, &wrong_key) } else { (sig, p) })
+            .collect::<Vec<_>>();
+        assert_eq!(RistrettoSchnorr::verify_same_message_batch(msg, &entries), vec![
+            true, true, false, true
+        ]);
+        assert!(RistrettoSchnorr::verify_same_message_batch(b"Jingo", &entries)
+            .iter()
+            .all(|ok| !ok));
+        assert!(RistrettoSchnorr::verify_same_message_batch(msg, &[]).is_empty());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn message_only_challenge_forgery_is_rejected() {
+        // With a challenge of e = H(m) alone, anyone can pick s and set R = s.G - e.P to "sign" for any key. The
+        // message challenge commits to R and P, so such a signature must not verify.
+        let mut rng = rand::thread_rng();
+        let (_, P) = RistrettoPublicKey::random_keypair(&mut rng);
+        let msg = b"Men at Arms";
+        let e = RistrettoSecretKey::from_bytes(&Blake256::digest(msg)).unwrap();
+        let s = RistrettoSecretKey::random(&mut rng);
+        let R = &RistrettoPublicKey::from_secret_key(&s) - &(&e * &P);
+        let forged = RistrettoSchnorr::new(R, s);
+        assert!(forged.verify(&P, &e));
+        assert!(!forged.verify_message(&P, msg));
+        assert_eq!(
+            RistrettoSchnorr::verify_same_message_batch(msg, &[(&forged, &P)]),
+            vec![false]
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn signature_subtraction() {
+        let mut rng = rand::thread_rng();
+        let (k1, P1) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r1, R1) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (k2, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let r2 = RistrettoSecretKey::random(&mut rng);
+        let e = Blake256::digest(b"Soul Music");
+        let s1 = RistrettoSchnorr::sign(k1, r1, &e).unwrap();
+        let s2 = RistrettoSchnorr::sign(k2, r2, &e).unwrap();
+        let s_agg = &s1 + &s2;
+        let recovered = &s_agg - &s2;
+        assert_eq!(recovered, s1);
+        assert_eq!(recovered.get_public_nonce(), &R1);
+        assert!(recovered.verify_challenge(&P1, &e));
+        // Every owned and borrowed combination gives the same result
+        assert_eq!(s_agg.clone() - &s2, s1);
+        assert_eq!(&s_agg - s2.clone(), s1);
+        assert_eq!(s_agg.clone() - s2.clone(), s1);
+        assert_eq!(s1.clone() + &s2, s_agg);
+        assert_eq!(s1 + s2, s_agg);
+    }
 }
@@ -40,6 +40,19 @@
 //!       digest_type: PhantomData<D>,
 //!   }
 //! ```
+//!
+//! ## Secret keys
+//!
+//! `Serialize` and `Deserialize` are only implemented for [RistrettoSecretKey](crate::ristretto::RistrettoSecretKey)
+//! when the `serde-secrets` feature is enabled. Serializing a key to JSON or another format is an easy way for it to
+//! end up in logs, caches or crash reports; with the feature off, structs holding secret keys cannot derive
+//! `Serialize` without an explicit opt-in. Public keys, commitments and signatures are always serializable.
+#![cfg_attr(not(feature = "serde-secrets"), doc = "```compile_fail")]
+#![cfg_attr(feature = "serde-secrets", doc = "```")]
+//! # use tari_crypto::ristretto::RistrettoSecretKey;
+//! let k = RistrettoSecretKey::default();
+//! let json = serde_json::to_string(&k).unwrap();
+//! ```
 
 use std::fmt;
 
@@ -50,9 +63,17 @@ use serde::{
     Serialize,
     Serializer,
 };
-use tari_utilities::{byte_array::ByteArray, hex::Hex};
+use tari_utilities::{byte_array::ByteArray, hex::Hex, message_format::MessageFormat};
+
+#[cfg(feature = "serde-secrets")]
+use crate::ristretto::RistrettoSecretKey;
+use crate::{
+    errors::SerializationError,
+    ristretto::{pedersen::PedersenCommitment, RistrettoPublicKey, RistrettoSchnorr},
+};
 
-use crate::ristretto::{RistrettoPublicKey, RistrettoSecretKey};
+/// The version byte that is prepended to the output of the `to_versioned_bytes` methods
+pub const SERIALIZATION_VERSION: u8 = 1;
 
 impl<'de> Deserialize<'de> for RistrettoPublicKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -92,6 +113,7 @@ impl Serialize for RistrettoPublicKey {
     }
 }
 
+#[cfg(feature = "serde-secrets")]
 impl<'de> Deserialize<'de> for RistrettoSecretKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where D: Deserializer<'de> {
@@ -119,6 +141,7 @@ impl<'de> Deserialize<'de> for RistrettoSecretKey {
     }
 }
 
+#[cfg(feature = "serde-secrets")]
 impl Serialize for RistrettoSecretKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer {
@@ -129,3 +152,311 @@ impl Serialize for RistrettoSecretKey {
         }
     }
 }
+
+//----------------------------------------   Versioned byte encodings   ----------------------------------------------//
+
+fn to_versioned(payloads: &[&[u8]]) -> Vec<u8> {
+    let mut result = vec![SERIALIZATION_VERSION];
+    for payload in payloads {
+        result.extend_from_slice(payload);
+    }
+    result
+}
+
+fn from_versioned(bytes: &[u8]) -> Result<&[u8], SerializationError> {
+    match bytes.split_first() {
+        Some((&SERIALIZATION_VERSION, payload)) => Ok(payload),
+        Some((&v, _)) => Err(SerializationError::VersionMismatch(v)),
+        None => Err(SerializationError::IncorrectLength),
+    }
+}
+
+impl RistrettoPublicKey {
+    /// Serialize the key as a version byte followed by its 32-byte compressed encoding. Use
+    /// [ByteArray::as_bytes] for the raw, unversioned encoding.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        to_versioned(&[self.as_bytes()])
+    }
+
+    /// Deserialize a key that was serialized with [RistrettoPublicKey::to_versioned_bytes]
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let payload = from_versioned(bytes)?;
+        Ok(RistrettoPublicKey::from_bytes(payload)?)
+    }
+}
+
+impl PedersenCommitment {
+    /// Serialize the commitment as a version byte followed by its 32-byte compressed encoding. Use
+    /// [ByteArray::as_bytes] for the raw, unversioned encoding.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        to_versioned(&[self.as_bytes()])
+    }
+
+    /// Deserialize a commitment that was serialized with [PedersenCommitment::to_versioned_bytes]
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let payload = from_versioned(bytes)?;
+        Ok(PedersenCommitment::from_bytes(payload)?)
+    }
+}
+
+//----------------------------------------   Bounded MessagePack decoding   -----------------------------------------//
+
+impl PedersenCommitment {
+    /// The MessagePack encoding of every commitment has the same length and the same header, so take both from the
+    /// encoding of the default commitment
+    fn binary_template() -> Result<Vec<u8>, SerializationError> {
+        PedersenCommitment::default()
+            .to_binary()
+            .map_err(|e| SerializationError::InvalidEncoding(e.to_string()))
+    }
+
+    /// Deserialize a commitment from the MessagePack encoding produced by [MessageFormat::to_binary], checking the
+    /// length and header of `buf` **before** decoding it. Unlike [MessageFormat::from_binary], crafted input, such as
+    /// a header claiming a huge payload, is rejected without allocating, so this should be preferred for parsing
+    /// untrusted data.
+    pub fn from_binary_bounded(buf: &[u8]) -> Result<Self, SerializationError> {
+        let template = PedersenCommitment::binary_template()?;
+        if buf.len() != template.len() {
+            return Err(SerializationError::IncorrectLength);
+        }
+        let header_len = template.len() - PedersenCommitment::default().as_bytes().len();
+        let (header, payload) = buf.split_at(header_len);
+        if header != &template[..header_len] {
+            return Err(SerializationError::InvalidEncoding(
+                "Unexpected MessagePack header for a commitment".to_string(),
+            ));
+        }
+        Ok(PedersenCommitment::from_bytes(payload)?)
+    }
+
+    /// Deserialize a commitment from the Base64 encoding produced by [MessageFormat::to_base64]. The length of the
+    /// string is checked before it is decoded, and the decoded bytes are checked as in
+    /// [from_binary_bounded](PedersenCommitment::from_binary_bounded).
+    pub fn from_base64_bounded(s: &str) -> Result<Self, SerializationError> {
+        let template = PedersenCommitment::binary_template()?;
+        if s.len() != base64::encode(&template).len() {
+            return Err(SerializationError::IncorrectLength);
+        }
+        let buf = base64::decode(s).map_err(|e| SerializationError::InvalidEncoding(e.to_string()))?;
+        PedersenCommitment::from_binary_bounded(&buf)
+    }
+}
+
+impl RistrettoSchnorr {
+    /// Serialize the signature as a version byte, followed by the 64-byte encoding produced by
+    /// [RistrettoSchnorr::to_bytes].
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        to_versioned(&[&self.to_bytes()])
+    }
+
+    /// Deserialize a signature that was serialized with [RistrettoSchnorr::to_versioned_bytes]
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let payload = from_versioned(bytes)?;
+        if payload.len() != 64 {
+            return Err(SerializationError::IncorrectLength);
+        }
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(payload);
+        Ok(RistrettoSchnorr::from_bytes(&signature)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_utilities::{
+        hex::{to_hex, Hex},
+        message_format::MessageFormat,
+        ByteArray,
+    };
+
+    use super::SERIALIZATION_VERSION;
+    use crate::{
+        commitment::HomomorphicCommitmentFactory,
+        errors::SerializationError,
+        keys::PublicKey,
+        ristretto::{
+            pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+            RistrettoPublicKey,
+            RistrettoSchnorr,
+            RistrettoSecretKey,
+        },
+    };
+
+    #[test]
+    fn versioned_public_key() {
+        let mut rng = rand::thread_rng();
+        let (_, p) = RistrettoPublicKey::random_keypair(&mut rng);
+        let mut bytes = p.to_versioned_bytes();
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(bytes[0], SERIALIZATION_VERSION);
+        assert_eq!(&bytes[1..], p.as_bytes());
+        assert_eq!(RistrettoPublicKey::from_versioned_bytes(&bytes).unwrap(), p);
+        bytes[0] += 1;
+        assert_eq!(
+            RistrettoPublicKey::from_versioned_bytes(&bytes),
+            Err(SerializationError::VersionMismatch(SERIALIZATION_VERSION + 1))
+        );
+        assert_eq!(
+            RistrettoPublicKey::from_versioned_bytes(&[]),
+            Err(SerializationError::IncorrectLength)
+        );
+    }
+
+    #[test]
+    fn versioned_commitment() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let c = PedersenCommitmentFactory::default().commit_value(&k, 42);
+        let mut bytes = c.to_versioned_bytes();
+        assert_eq!(PedersenCommitment::from_versioned_bytes(&bytes).unwrap(), c);
+        bytes[0] += 1;
+        assert_eq!(
+            PedersenCommitment::from_versioned_bytes(&bytes),
+            Err(SerializationError::VersionMismatch(SERIALIZATION_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn versioned_signature() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign(k, r, &[1u8; 32]).unwrap();
+        let mut bytes = sig.to_versioned_bytes();
+        assert_eq!(bytes.len(), 65);
+        assert_eq!(RistrettoSchnorr::from_versioned_bytes(&bytes).unwrap(), sig);
+        assert_eq!(
+            RistrettoSchnorr::from_versioned_bytes(&bytes[..64]),
+            Err(SerializationError::IncorrectLength)
+        );
+        bytes[0] += 1;
+        assert_eq!(
+            RistrettoSchnorr::from_versioned_bytes(&bytes),
+            Err(SerializationError::VersionMismatch(SERIALIZATION_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn bounded_binary_commitment() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let c = PedersenCommitmentFactory::default().commit_value(&k, 42);
+        // Valid
+        let buf = c.to_binary().unwrap();
+        assert_eq!(PedersenCommitment::from_binary_bounded(&buf), Ok(c.clone()));
+        // Truncated
+        assert_eq!(
+            PedersenCommitment::from_binary_bounded(&buf[..buf.len() - 1]),
+            Err(SerializationError::IncorrectLength)
+        );
+        assert_eq!(
+            PedersenCommitment::from_binary_bounded(&[]),
+            Err(SerializationError::IncorrectLength)
+        );
+        // Oversized
+        let mut long = buf.clone();
+        long.push(0);
+        assert_eq!(
+            PedersenCommitment::from_binary_bounded(&long),
+            Err(SerializationError::IncorrectLength)
+        );
+        // A bin32 header claiming a 4GB payload, padded to the expected length
+        let mut huge = vec![0xc6, 0xff, 0xff, 0xff, 0xff];
+        huge.resize(buf.len(), 0);
+        assert!(matches!(
+            PedersenCommitment::from_binary_bounded(&huge),
+            Err(SerializationError::InvalidEncoding(_))
+        ));
+        // A valid header with an invalid point
+        let mut bad_point = buf.clone();
+        let n = bad_point.len();
+        bad_point[n - 32..].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            PedersenCommitment::from_binary_bounded(&bad_point),
+            Err(SerializationError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn bounded_base64_commitment() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let c = PedersenCommitmentFactory::default().commit_value(&k, 42);
+        let s = c.to_base64().unwrap();
+        assert_eq!(PedersenCommitment::from_base64_bounded(&s), Ok(c));
+        assert_eq!(
+            PedersenCommitment::from_base64_bounded(&s[..s.len() - 4]),
+            Err(SerializationError::IncorrectLength)
+        );
+        assert_eq!(
+            PedersenCommitment::from_base64_bounded(&format!("{}AAAA", s)),
+            Err(SerializationError::IncorrectLength)
+        );
+        let bad = "@".repeat(s.len());
+        assert!(matches!(
+            PedersenCommitment::from_base64_bounded(&bad),
+            Err(SerializationError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn signatures_serialize_without_secret_keys() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"Jingo");
+        let json = sig.to_json().unwrap();
+        assert!(json.contains(&sig.get_signature().to_hex()));
+        assert_eq!(RistrettoSchnorr::from_json(&json).unwrap(), sig);
+        let bin = sig.to_binary().unwrap();
+        assert_eq!(RistrettoSchnorr::from_binary(&bin).unwrap(), sig);
+    }
+
+    #[test]
+    fn schnorr_json_has_named_fields() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (r, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let sig = RistrettoSchnorr::sign_message(&k, &r, b"named fields");
+        let json = sig.to_json().unwrap();
+        let expected = format!(
+            r#"{{"public_nonce":"{}","signature":"{}"}}"#,
+            sig.get_public_nonce().to_hex(),
+            sig.get_signature().to_hex()
+        );
+        assert_eq!(json, expected);
+        assert_eq!(RistrettoSchnorr::from_json(&json).unwrap(), sig);
+        assert!(RistrettoSchnorr::from_json(r#"{"public_nonce":"00","signature":"00"}"#).is_err());
+    }
+
+    #[test]
+    fn schnorr_binary_layout() {
+        // The binary layout is that of the original derived implementation: a struct of two length-prefixed byte
+        // strings. Changing it would break every signature that has already been stored or sent.
+        let nonce = "8063d85e151abee630e643e2b3dc47bfaeb8aa859c9d10d60847985f286aad19";
+        let scalar = "0fd60e6479507fec35a46d2ec9da0ae300e9202e613e99b8f2b01d7ef6eccc02";
+        let sig = RistrettoSchnorr::new(
+            RistrettoPublicKey::from_hex(nonce).unwrap(),
+            RistrettoSecretKey::from_hex(scalar).unwrap(),
+        );
+        let bin = bincode::serialize(&sig).unwrap();
+        let expected = format!("2000000000000000{}2000000000000000{}", nonce, scalar);
+        assert_eq!(to_hex(&bin), expected);
+        assert_eq!(bin.len(), 80);
+        let decoded: RistrettoSchnorr = bincode::deserialize(&bin).unwrap();
+        assert_eq!(decoded, sig);
+        assert!(bincode::deserialize::<RistrettoSchnorr>(&bin[..79]).is_err());
+    }
+
+    #[cfg(feature = "serde-secrets")]
+    #[test]
+    fn secret_key_round_trip() {
+        let mut rng = rand::thread_rng();
+        let (k, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let json = k.to_json().unwrap();
+        assert_eq!(json, format!("\"{}\"", k.to_hex()));
+        assert_eq!(RistrettoSecretKey::from_json(&json).unwrap(), k);
+        let bin = k.to_binary().unwrap();
+        assert_eq!(RistrettoSecretKey::from_binary(&bin).unwrap(), k);
+    }
+}
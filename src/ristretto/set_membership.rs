@@ -0,0 +1,216 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Proofs that a Pedersen commitment hides one of a public set of values, without revealing which
+//!
+//! For a commitment \\( C = k.G + v.H \\) and a set \\( \\{v_0, \dots, v_{n-1}\\} \\), let \\( P_j = C - v_j.H \\).
+//! If \\( v = v_i \\) then \\( P_i = k.G \\), and the prover knows its discrete log with respect to _G_, while for
+//! every other _j_ the discrete log of \\( P_j \\) is unknown to everyone. A `MembershipProof` is a one-of-many
+//! (OR) composition of Schnorr proofs over the \\( P_j \\), in the style of Cramer, Damgård and Schoenmakers: the
+//! prover simulates the proofs for every \\( j \neq i \\) by choosing their challenges \\( e_j \\) and responses
+//! \\( s_j \\) up front, and completes the real proof with the challenge \\( e_i = e - \sum_{j \neq i} e_j \\), where
+//! _e_ is the Fiat-Shamir challenge over the statement and all of the nonces,
+//! \\( R_j = s_j.G - e_j.P_j \\). A verifier recomputes the nonces and checks that the challenges sum to _e_.
+//!
+//! The proof holds a challenge and a response for every member of the set, so its size is linear in the size of the
+//! set.
+
+use digest::Digest;
+use rand::{CryptoRng, Rng};
+use tari_utilities::ByteArray;
+
+use crate::{
+    errors::CommitmentError,
+    hash::blake2::Blake256,
+    keys::SecretKey,
+    ristretto::{
+        output_mask::OutputMask,
+        pedersen::{PedersenCommitment, PedersenCommitmentFactory},
+        RistrettoPublicKey,
+        RistrettoSecretKey,
+    },
+};
+
+const MEMBERSHIP_CHALLENGE_PERSONA: &[u8] = b"tari.set_member";
+
+/// A non-interactive proof that a Pedersen commitment hides one of a public set of values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MembershipProof {
+    challenges: Vec<RistrettoSecretKey>,
+    responses: Vec<RistrettoSecretKey>,
+}
+
+impl MembershipProof {
+    /// Prove that `commitment`, which opens with `opening`, commits to a value in `set` using the generators of
+    /// `factory`. Returns `ValueNotInSet` if the value of `opening` is not in `set`.
+    ///
+    /// The proof only verifies if `opening` really opens `commitment`.
+    #[allow(non_snake_case)]
+    pub fn prove_membership<R: Rng + CryptoRng>(
+        factory: &PedersenCommitmentFactory,
+        commitment: &PedersenCommitment,
+        opening: &OutputMask,
+        set: &[u64],
+        rng: &mut R,
+    ) -> Result<Self, CommitmentError> {
+        let index = set
+            .iter()
+            .position(|&v| v == opening.value())
+            .ok_or(CommitmentError::ValueNotInSet)?;
+        let points = MembershipProof::statement_points(factory, commitment, set);
+        let mut challenges = Vec::with_capacity(set.len());
+        let mut responses = Vec::with_capacity(set.len());
+        let mut nonces = Vec::with_capacity(set.len());
+        let r = RistrettoSecretKey::random(rng);
+        for (j, P) in points.iter().enumerate() {
+            if j == index {
+                challenges.push(RistrettoSecretKey::default());
+                responses.push(RistrettoSecretKey::default());
+                nonces.push(RistrettoPublicKey::new_from_pk(r.0 * factory.G));
+            } else {
+                let e = RistrettoSecretKey::random(rng);
+                let s = RistrettoSecretKey::random(rng);
+                nonces.push(MembershipProof::simulated_nonce(factory, P, &e, &s));
+                challenges.push(e);
+                responses.push(s);
+            }
+        }
+        let e = MembershipProof::challenge(factory, commitment, set, &nonces);
+        let e_i = challenges.iter().fold(e, |acc, e_j| &acc - e_j);
+        responses[index] = &r + &(&e_i * opening.blinding());
+        challenges[index] = e_i;
+        Ok(MembershipProof { challenges, responses })
+    }
+
+    /// Returns true if this proof shows that `commitment` hides one of the values in `set`, using the generators of
+    /// `factory`. The set must be the same, in the same order, as the one the proof was made for.
+    pub fn verify_membership(
+        &self,
+        factory: &PedersenCommitmentFactory,
+        commitment: &PedersenCommitment,
+        set: &[u64],
+    ) -> bool {
+        if set.is_empty() || self.challenges.len() != set.len() || self.responses.len() != set.len() {
+            return false;
+        }
+        let points = MembershipProof::statement_points(factory, commitment, set);
+        let nonces = points
+            .iter()
+            .zip(self.challenges.iter().zip(self.responses.iter()))
+            .map(|(p, (e, s))| MembershipProof::simulated_nonce(factory, p, e, s))
+            .collect::<Vec<_>>();
+        let e = MembershipProof::challenge(factory, commitment, set, &nonces);
+        let sum = self
+            .challenges
+            .iter()
+            .fold(RistrettoSecretKey::default(), |acc, e_j| &acc + e_j);
+        sum == e
+    }
+
+    /// The points \\( P_j = C - v_j.H \\), one of which is a public key for the blinding factor
+    fn statement_points(
+        factory: &PedersenCommitmentFactory,
+        commitment: &PedersenCommitment,
+        set: &[u64],
+    ) -> Vec<RistrettoPublicKey> {
+        set.iter()
+            .map(|&v| factory.commitment_minus_value(commitment, v))
+            .collect()
+    }
+
+    /// The nonce \\( R = s.G - e.P \\) that makes \\( (R, e, s) \\) a valid Schnorr transcript for _P_
+    #[allow(non_snake_case)]
+    fn simulated_nonce(
+        factory: &PedersenCommitmentFactory,
+        P: &RistrettoPublicKey,
+        e: &RistrettoSecretKey,
+        s: &RistrettoSecretKey,
+    ) -> RistrettoPublicKey {
+        let sG = RistrettoPublicKey::new_from_pk(s.0 * factory.G);
+        &sG - &(e * P)
+    }
+
+    fn challenge(
+        factory: &PedersenCommitmentFactory,
+        commitment: &PedersenCommitment,
+        set: &[u64],
+        nonces: &[RistrettoPublicKey],
+    ) -> RistrettoSecretKey {
+        let hasher = Blake256::with_params(&[], &[], MEMBERSHIP_CHALLENGE_PERSONA)
+            .chain(factory.G.compress().as_bytes())
+            .chain(factory.H.compress().as_bytes())
+            .chain(commitment.as_bytes());
+        let hasher = set.iter().fold(hasher, |h, v| h.chain(v.to_le_bytes()));
+        let hash = nonces.iter().fold(hasher, |h, r| h.chain(r.as_bytes())).finalize();
+        RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup(value: u64) -> (PedersenCommitmentFactory, PedersenCommitment, OutputMask) {
+        let factory = PedersenCommitmentFactory::default();
+        let opening = OutputMask::new(value, RistrettoSecretKey::random(&mut rand::thread_rng()));
+        let commitment = opening.commitment(&factory);
+        (factory, commitment, opening)
+    }
+
+    #[test]
+    fn member_of_set_verifies() {
+        let mut rng = rand::thread_rng();
+        let set = [5, 10, 25, 50, 100];
+        for &v in &set {
+            let (factory, c, opening) = setup(v);
+            let proof = MembershipProof::prove_membership(&factory, &c, &opening, &set, &mut rng).unwrap();
+            assert!(proof.verify_membership(&factory, &c, &set));
+            // The proof is bound to the set and the commitment
+            assert!(!proof.verify_membership(&factory, &c, &[5, 10, 25, 50, 101]));
+            assert!(!proof.verify_membership(&factory, &c, &set[..4]));
+            let (_, other, _) = setup(v);
+            assert!(!proof.verify_membership(&factory, &other, &set));
+        }
+        let (factory, c, opening) = setup(7);
+        let proof = MembershipProof::prove_membership(&factory, &c, &opening, &[7], &mut rng).unwrap();
+        assert!(proof.verify_membership(&factory, &c, &[7]));
+    }
+
+    #[test]
+    fn non_member_fails() {
+        let mut rng = rand::thread_rng();
+        let set = [5, 10, 25];
+        let (factory, c, opening) = setup(11);
+        assert_eq!(
+            MembershipProof::prove_membership(&factory, &c, &opening, &set, &mut rng),
+            Err(CommitmentError::ValueNotInSet)
+        );
+        assert_eq!(
+            MembershipProof::prove_membership(&factory, &c, &opening, &[], &mut rng),
+            Err(CommitmentError::ValueNotInSet)
+        );
+        // Lying about the value in the opening produces a proof that does not verify
+        let lie = OutputMask::new(10, opening.blinding().clone());
+        let proof = MembershipProof::prove_membership(&factory, &c, &lie, &set, &mut rng).unwrap();
+        assert!(!proof.verify_membership(&factory, &c, &set));
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! One-time "stealth" addresses derived via ECDH
+//!
+//! A recipient publishes two public keys: a scan key, \\( A = a.G \\), and a spend key, \\( B = b.G \\). To pay the
+//! recipient, a sender chooses a random secret, _r_, and derives the one-time public key of output _i_
+//! $$
+//!   P_i = H(r.A || i).G + B
+//! $$
+//! publishing \\( R = r.G \\) alongside the outputs. Since \\( r.A = a.R \\), the recipient can use their scan
+//! secret to detect outputs addressed to them, and their spend secret to recover the one-time secret key
+//! \\( p_i = H(a.R || i) + b \\). Observers see only \\( P_i \\) and _R_, and cannot link either to the recipient's
+//! published keys.
+//!
+//! The output index, _i_, lets a sender pay several outputs, to the same or to different recipients, under one _R_:
+//! without it, two outputs to the same recipient would share a one-time key.
+
+use digest::Digest;
+use tari_utilities::ByteArray;
+
+use crate::{
+    hash::blake2::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey},
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+};
+
+const STEALTH_PERSONA: &[u8] = b"tari.stealth";
+
+/// Hash the ECDH shared secret and the output index to a scalar
+fn shared_secret_scalar(shared_secret: &RistrettoPublicKey, output_index: u64) -> RistrettoSecretKey {
+    let hash = Blake256::with_params(&[], &[], STEALTH_PERSONA)
+        .chain(shared_secret.as_bytes())
+        .chain(output_index.to_le_bytes())
+        .finalize();
+    RistrettoSecretKey::from_bytes(&hash).expect("Blake256 produces a 32 byte digest")
+}
+
+/// Sender side: derive the one-time public key of output `output_index` for the recipient with the given scan and
+/// spend public keys. Returns the one-time public key, \\( P_i \\), and the sender's public nonce, _R_, which must be
+/// published with the output so that the recipient can find it. `sender_secret` MUST be random and MUST NOT be reused
+/// in another transaction; within one transaction, every output MUST have a different `output_index`.
+pub fn generate_one_time_key(
+    sender_secret: &RistrettoSecretKey,
+    recipient_scan_pub: &RistrettoPublicKey,
+    recipient_spend_pub: &RistrettoPublicKey,
+    output_index: u64,
+) -> (RistrettoPublicKey, RistrettoPublicKey) {
+    let shared_secret = RistrettoPublicKey::shared_secret(sender_secret, recipient_scan_pub);
+    let h = shared_secret_scalar(&shared_secret, output_index);
+    let one_time_pub = &RistrettoPublicKey::from_secret_key(&h) + recipient_spend_pub;
+    let sender_nonce_pub = RistrettoPublicKey::from_secret_key(sender_secret);
+    (one_time_pub, sender_nonce_pub)
+}
+
+/// Recipient side: recover the secret key, \\( p_i \\), of the one-time public key of output `output_index` from the
+/// sender's public nonce
+pub fn recover_one_time_secret(
+    scan_secret: &RistrettoSecretKey,
+    spend_secret: &RistrettoSecretKey,
+    sender_nonce_pub: &RistrettoPublicKey,
+    output_index: u64,
+) -> RistrettoSecretKey {
+    let shared_secret = RistrettoPublicKey::shared_secret(scan_secret, sender_nonce_pub);
+    &shared_secret_scalar(&shared_secret, output_index) + spend_secret
+}
+
+/// Recipient side: returns true if `one_time_pub`, the key of output `output_index`, was addressed to the owner of
+/// `scan_secret` and `spend_pub`. Only the scan secret is required, so detection can be delegated without giving away
+/// the ability to spend.
+pub fn is_addressed_to(
+    scan_secret: &RistrettoSecretKey,
+    spend_pub: &RistrettoPublicKey,
+    sender_nonce_pub: &RistrettoPublicKey,
+    one_time_pub: &RistrettoPublicKey,
+    output_index: u64,
+) -> bool {
+    let shared_secret = RistrettoPublicKey::shared_secret(scan_secret, sender_nonce_pub);
+    let h = shared_secret_scalar(&shared_secret, output_index);
+    &RistrettoPublicKey::from_secret_key(&h) + spend_pub == *one_time_pub
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        keys::PublicKey,
+        ristretto::{
+            stealth::{generate_one_time_key, is_addressed_to, recover_one_time_secret},
+            RistrettoPublicKey,
+        },
+    };
+
+    #[test]
+    fn sender_and_recipient_agree() {
+        let mut rng = rand::thread_rng();
+        let (scan_secret, scan_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (spend_secret, spend_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (sender_secret, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (one_time_pub, sender_nonce_pub) = generate_one_time_key(&sender_secret, &scan_pub, &spend_pub, 0);
+        assert_ne!(one_time_pub, spend_pub);
+        assert!(is_addressed_to(
+            &scan_secret,
+            &spend_pub,
+            &sender_nonce_pub,
+            &one_time_pub,
+            0
+        ));
+        let one_time_secret = recover_one_time_secret(&scan_secret, &spend_secret, &sender_nonce_pub, 0);
+        assert_eq!(RistrettoPublicKey::from_secret_key(&one_time_secret), one_time_pub);
+    }
+
+    #[test]
+    fn outputs_to_one_recipient_get_distinct_keys() {
+        let mut rng = rand::thread_rng();
+        let (scan_secret, scan_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (spend_secret, spend_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (sender_secret, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (p0, nonce0) = generate_one_time_key(&sender_secret, &scan_pub, &spend_pub, 0);
+        let (p1, nonce1) = generate_one_time_key(&sender_secret, &scan_pub, &spend_pub, 1);
+        assert_eq!(nonce0, nonce1);
+        assert_ne!(p0, p1);
+        for (i, p) in [(0, &p0), (1, &p1)] {
+            assert!(is_addressed_to(&scan_secret, &spend_pub, &nonce0, p, i));
+            assert!(!is_addressed_to(&scan_secret, &spend_pub, &nonce0, p, 1 - i));
+            let secret = recover_one_time_secret(&scan_secret, &spend_secret, &nonce0, i);
+            assert_eq!(&RistrettoPublicKey::from_secret_key(&secret), p);
+        }
+    }
+
+    #[test]
+    fn other_recipients_do_not_detect_output() {
+        let mut rng = rand::thread_rng();
+        let (_, scan_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, spend_pub) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (other_scan_secret, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (sender_secret, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (one_time_pub, sender_nonce_pub) = generate_one_time_key(&sender_secret, &scan_pub, &spend_pub, 0);
+        assert!(!is_addressed_to(
+            &other_scan_secret,
+            &spend_pub,
+            &sender_nonce_pub,
+            &one_time_pub,
+            0
+        ));
+    }
+}
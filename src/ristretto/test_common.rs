@@ -20,9 +20,18 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use curve25519_dalek::{constants::BASEPOINT_ORDER, ristretto::RistrettoPoint};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
 use crate::{
     keys::{PublicKey, SecretKey},
-    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    ristretto::{
+        constants::RISTRETTO_NUMS_POINTS,
+        pedersen::RISTRETTO_PEDERSEN_G,
+        RistrettoPublicKey,
+        RistrettoSecretKey,
+    },
 };
 
 pub(crate) fn get_keypair() -> (RistrettoSecretKey, RistrettoPublicKey) {
@@ -31,3 +40,59 @@ pub(crate) fn get_keypair() -> (RistrettoSecretKey, RistrettoPublicKey) {
     let pk = RistrettoPublicKey::from_secret_key(&k);
     (k, pk)
 }
+
+/// A deterministic RNG for tests. Randomised tests that use it with a fixed seed are reproducible, and a failing case
+/// found with a varying seed can be replayed by hard-coding that seed.
+pub(crate) fn seeded_rng(seed: u64) -> impl RngCore + CryptoRng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+/// Add the group order, _l_, to the little-endian scalar `s`, giving an unreduced encoding of the same value for
+/// tests of canonical decoding
+pub(crate) fn add_group_order(s: &[u8]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    let mut carry = 0u16;
+    for ((r, a), b) in result.iter_mut().zip(s).zip(&BASEPOINT_ORDER.to_bytes()) {
+        let sum = u16::from(*a) + u16::from(*b) + carry;
+        *r = sum.to_le_bytes()[0];
+        carry = sum >> 8;
+    }
+    result
+}
+
+/// A point with no known discrete log relative to any generator used in the crate, for tests that need an independent
+/// base, e.g. adaptor signatures and DLEQ proofs. Random bytes are hashed to the group, and the result is guaranteed
+/// to differ from _G_ and from every NUMS point, including _H_.
+pub(crate) fn random_independent_point<R: RngCore + CryptoRng>(rng: &mut R) -> RistrettoPublicKey {
+    loop {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        let p = RistrettoPoint::from_uniform_bytes(&bytes);
+        if p != RISTRETTO_PEDERSEN_G && !RISTRETTO_NUMS_POINTS.contains(&p) {
+            return RistrettoPublicKey::new_from_pk(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ristretto::pedersen::RISTRETTO_PEDERSEN_H;
+
+    #[test]
+    fn random_independent_point_is_not_a_generator() {
+        let mut rng = seeded_rng(0x5eed);
+        let g = RistrettoPublicKey::new_from_pk(RISTRETTO_PEDERSEN_G);
+        let h = RistrettoPublicKey::new_from_pk(*RISTRETTO_PEDERSEN_H);
+        let p1 = random_independent_point(&mut rng);
+        let p2 = random_independent_point(&mut rng);
+        for p in &[&p1, &p2] {
+            assert_ne!(*p, &g);
+            assert_ne!(*p, &h);
+            assert!(RISTRETTO_NUMS_POINTS
+                .iter()
+                .all(|n| *p != &RistrettoPublicKey::new_from_pk(*n)));
+        }
+        assert_ne!(p1, p2);
+    }
+}
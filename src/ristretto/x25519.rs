@@ -0,0 +1,163 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! X25519 keys for interoperating with X25519-based protocols, such as Noise transports
+//!
+//! ## Why there is no Ristretto to X25519 conversion
+//! A Ristretto point is an equivalence class of Edwards points that differ by a small torsion component. Each class
+//! has several Edwards representatives, and they map to different Montgomery u-coordinates. So a Ristretto public key
+//! has no single, well-defined X25519 encoding. X25519 also clamps its secret scalars, so the same 32 secret bytes
+//! give unrelated keys in the two systems. A mapping that happens to work for one implementation's internal
+//! representation would be fragile and easy to misuse.
+//!
+//! Instead, a separate X25519 key pair is derived from the same 32-byte seed as
+//! [keypair_from_seed](crate::ristretto::RistrettoPublicKey::keypair_from_seed), with a different domain separation
+//! label. The seed is hashed with `Blake2b`, and the first 32 bytes of the digest are the X25519 secret key. This
+//! means one seed can back both a Ristretto identity and an X25519 transport key. Compromise of the seed compromises
+//! both, but neither key reveals the other.
+//!
+//! The keys follow RFC 7748: secret keys are clamped when they are used, and the public key is the u-coordinate of
+//! the clamped secret times the base point, _u = 9_.
+
+use blake2::Blake2b;
+use curve25519_dalek::{constants::X25519_BASEPOINT, montgomery::MontgomeryPoint, scalar::Scalar};
+use digest::Digest;
+use zeroize::Zeroize;
+
+const X25519_SEED_LABEL: &[u8] = b"tari.x25519_from_seed";
+
+/// An X25519 secret key. The key material is cleared when it is dropped.
+#[derive(Clone)]
+pub struct X25519SecretKey([u8; 32]);
+
+/// An X25519 public key, i.e. the 32-byte little-endian u-coordinate of a point on Curve25519
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct X25519PublicKey([u8; 32]);
+
+impl X25519SecretKey {
+    /// Wrap 32 bytes of secret key material. The bytes are clamped when the key is used, as specified by RFC 7748.
+    pub fn new(bytes: [u8; 32]) -> Self {
+        X25519SecretKey(bytes)
+    }
+
+    /// The public key for this secret key
+    pub fn public_key(&self) -> X25519PublicKey {
+        X25519PublicKey((self.clamped_scalar() * X25519_BASEPOINT).to_bytes())
+    }
+
+    /// Calculate the X25519 shared secret with `public_key`. Both parties calculate the same 32 bytes. The result is
+    /// all zeroes if `public_key` is a low-order point, which callers should reject if contributory behaviour matters.
+    pub fn diffie_hellman(&self, public_key: &X25519PublicKey) -> [u8; 32] {
+        (self.clamped_scalar() * MontgomeryPoint(public_key.0)).to_bytes()
+    }
+
+    fn clamped_scalar(&self) -> Scalar {
+        let mut bytes = self.0;
+        bytes[0] &= 248;
+        bytes[31] &= 127;
+        bytes[31] |= 64;
+        let scalar = Scalar::from_bits(bytes);
+        bytes.zeroize();
+        scalar
+    }
+}
+
+impl Drop for X25519SecretKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl X25519PublicKey {
+    /// Wrap the given u-coordinate encoding
+    pub fn new(bytes: [u8; 32]) -> Self {
+        X25519PublicKey(bytes)
+    }
+
+    /// The 32-byte u-coordinate encoding of the public key
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Deterministically derive an X25519 key pair from a 32-byte seed, independently of the Ristretto key pair derived
+/// from the same seed. See the [module documentation](self) for details.
+pub fn x25519_keypair_from_seed(seed: &[u8; 32]) -> (X25519SecretKey, X25519PublicKey) {
+    let digest = Blake2b::new().chain(X25519_SEED_LABEL).chain(seed).finalize();
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&digest[..32]);
+    let secret = X25519SecretKey::new(bytes);
+    bytes.zeroize();
+    let public = secret.public_key();
+    (secret, public)
+}
+
+#[cfg(test)]
+mod test {
+    use tari_utilities::hex::{from_hex, to_hex};
+
+    use super::*;
+    use crate::ristretto::RistrettoPublicKey;
+
+    fn bytes_from_hex(hex: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&from_hex(hex).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn rfc7748_test_vector() {
+        let alice = X25519SecretKey::new(bytes_from_hex(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let bob = X25519SecretKey::new(bytes_from_hex(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+        assert_eq!(
+            to_hex(alice.public_key().as_bytes()),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            to_hex(bob.public_key().as_bytes()),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+        let shared = alice.diffie_hellman(&bob.public_key());
+        assert_eq!(
+            to_hex(&shared),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+        assert_eq!(bob.diffie_hellman(&alice.public_key()), shared);
+    }
+
+    #[test]
+    fn derived_keys_agree() {
+        let (a, a_pub) = x25519_keypair_from_seed(&[1u8; 32]);
+        let (b, b_pub) = x25519_keypair_from_seed(&[2u8; 32]);
+        assert_ne!(a_pub, b_pub);
+        assert_eq!(a.diffie_hellman(&b_pub), b.diffie_hellman(&a_pub));
+        // Derivation is deterministic, and independent of the Ristretto key pair from the same seed
+        let (_, a_pub2) = x25519_keypair_from_seed(&[1u8; 32]);
+        assert_eq!(a_pub, a_pub2);
+        let (k, _) = RistrettoPublicKey::keypair_from_seed(&[1u8; 32]);
+        assert_ne!(k.to_bytes(), a.0);
+    }
+}
@@ -69,7 +69,9 @@ pub enum CommitmentSignatureError {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitmentSignature<P, K> {
     public_nonce: HomomorphicCommitment<P>,
+    #[serde(with = "super::signature_scalar")]
     u: K,
+    #[serde(with = "super::signature_scalar")]
     v: K,
 }
 
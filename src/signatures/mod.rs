@@ -16,7 +16,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod commitment_signature;
+mod partial_signature;
 mod schnorr;
+pub(crate) mod signature_scalar;
 
 pub use commitment_signature::*;
+pub use partial_signature::*;
 pub use schnorr::*;
@@ -0,0 +1,171 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Partial signatures for interactive, multi-party Schnorr signing.
+
+use crate::{
+    keys::{PublicKey, SecretKey},
+    signatures::{SchnorrSignature, SchnorrSignatureError},
+};
+
+/// A single signer's contribution to a multi-party Schnorr signature.
+///
+/// Each of the _n_ signers publishes a nonce \\( R_i \\), and the aggregate nonce \\( R = \sum R_i \\) and aggregate
+/// public key \\( P = \sum P_i \\) are used to calculate a common challenge, _e_. Each signer then produces
+/// $$ s_i = r_i + e.k_i $$
+/// and the partial signatures are [combined](PartialSignature::combine) into the Schnorr signature
+/// \\( (R, \sum s_i) \\), which is valid for _P_ and _e_.
+///
+/// Plain key summation like this is vulnerable to rogue-key attacks unless every signer has proven knowledge of their
+/// secret key; use MuSig-style key aggregation if that is not the case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialSignature<P, K> {
+    signature: K,
+    public_key: P,
+    public_nonce: P,
+}
+
+impl<P, K> PartialSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    K: SecretKey,
+{
+    /// Create a partial signature, \\( s_i \\), from the signer with public key \\( P_i \\). `public_nonce` is the
+    /// aggregate nonce, _R_, that was used to calculate the challenge, not the signer's own nonce.
+    pub fn new(signature: K, public_key: P, public_nonce: P) -> Self {
+        Self {
+            signature,
+            public_key,
+            public_nonce,
+        }
+    }
+
+    /// Returns a reference to the partial signature scalar
+    pub fn get_signature(&self) -> &K {
+        &self.signature
+    }
+
+    /// Returns a reference to the public key of the signer that made this partial signature
+    pub fn get_public_key(&self) -> &P {
+        &self.public_key
+    }
+
+    /// Returns a reference to the aggregate public nonce that this partial signature was made against
+    pub fn get_public_nonce(&self) -> &P {
+        &self.public_nonce
+    }
+
+    /// Combine partial signatures into a Schnorr signature for the aggregate nonce `public_nonce`. Every part must
+    /// have been made against `public_nonce`, otherwise `MismatchedNonce` is returned.
+    ///
+    /// The result verifies against the sum of the signers' public keys, which is returned by
+    /// [aggregate_public_key](PartialSignature::aggregate_public_key).
+    pub fn combine(
+        public_nonce: &P,
+        parts: &[PartialSignature<P, K>],
+    ) -> Result<SchnorrSignature<P, K>, SchnorrSignatureError> {
+        if parts.is_empty() {
+            return Err(SchnorrSignatureError::NoPartialSignatures);
+        }
+        if parts.iter().any(|p| &p.public_nonce != public_nonce) {
+            return Err(SchnorrSignatureError::MismatchedNonce);
+        }
+        let s = parts.iter().fold(K::default(), |acc, p| acc + p.signature.clone());
+        Ok(SchnorrSignature::new(public_nonce.clone(), s))
+    }
+
+    /// The sum of the public keys of the signers of `parts`
+    pub fn aggregate_public_key(parts: &[PartialSignature<P, K>]) -> P {
+        parts.iter().fold(P::default(), |acc, p| acc + p.public_key.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use digest::Digest;
+    use tari_utilities::ByteArray;
+
+    use crate::{
+        common::Blake256,
+        keys::PublicKey,
+        ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+        signatures::{PartialSignature, SchnorrSignatureError},
+    };
+
+    type RistrettoPartialSignature = PartialSignature<RistrettoPublicKey, RistrettoSecretKey>;
+
+    #[allow(non_snake_case)]
+    fn three_party_signature(msg: &[u8]) -> (RistrettoPublicKey, RistrettoPublicKey, Vec<RistrettoPartialSignature>) {
+        let mut rng = rand::thread_rng();
+        let keys: Vec<_> = (0..3).map(|_| RistrettoPublicKey::random_keypair(&mut rng)).collect();
+        let nonces: Vec<_> = (0..3).map(|_| RistrettoPublicKey::random_keypair(&mut rng)).collect();
+        let R = nonces
+            .iter()
+            .fold(RistrettoPublicKey::default(), |acc, (_, r)| &acc + r);
+        let P = keys.iter().fold(RistrettoPublicKey::default(), |acc, (_, p)| &acc + p);
+        let e = Blake256::new()
+            .chain(R.as_bytes())
+            .chain(P.as_bytes())
+            .chain(msg)
+            .finalize();
+        let e = RistrettoSecretKey::from_bytes(&e).unwrap();
+        let parts = keys
+            .iter()
+            .zip(nonces.iter())
+            .map(|((k, p), (r, _))| PartialSignature::new(r + &(&e * k), p.clone(), R.clone()))
+            .collect();
+        (R, P, parts)
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn combine_partial_signatures() {
+        let (R, P, parts) = three_party_signature(b"Small Gods");
+        assert_eq!(RistrettoPartialSignature::aggregate_public_key(&parts), P);
+        let sig: RistrettoSchnorr = PartialSignature::combine(&R, &parts).unwrap();
+        assert_eq!(sig.get_public_nonce(), &R);
+        assert!(sig.verify_message(&P, b"Small Gods"));
+        // Two of the three parts are not enough
+        let sig = PartialSignature::combine(&R, &parts[..2]).unwrap();
+        assert!(!sig.verify_message(&P, b"Small Gods"));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn combine_rejects_mismatched_nonces() {
+        let (R, _, mut parts) = three_party_signature(b"Small Gods");
+        let (R2, _, _) = three_party_signature(b"Small Gods");
+        assert_eq!(
+            RistrettoPartialSignature::combine(&R2, &parts),
+            Err(SchnorrSignatureError::MismatchedNonce)
+        );
+        parts[1] = PartialSignature::new(parts[1].get_signature().clone(), parts[1].get_public_key().clone(), R2);
+        assert_eq!(
+            RistrettoPartialSignature::combine(&R, &parts),
+            Err(SchnorrSignatureError::MismatchedNonce)
+        );
+        assert_eq!(
+            RistrettoPartialSignature::combine(&R, &[]),
+            Err(SchnorrSignatureError::NoPartialSignatures)
+        );
+    }
+}
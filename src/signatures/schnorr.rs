@@ -4,19 +4,38 @@
 
 use std::{
     cmp::Ordering,
-    ops::{Add, Mul},
+    fmt,
+    ops::{Add, Mul, Sub},
 };
 
-use serde::{Deserialize, Serialize};
-use tari_utilities::ByteArray;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use tari_utilities::{hex::Hex, ByteArray};
 use thiserror::Error;
 
+use super::signature_scalar::{canonical_scalar_from_bytes, canonical_scalar_from_hex};
 use crate::keys::{PublicKey, SecretKey};
 
 #[derive(Clone, Debug, Error, PartialEq, Eq, Deserialize, Serialize)]
 pub enum SchnorrSignatureError {
     #[error("An invalid challenge was provided")]
     InvalidChallenge,
+    #[error("The public nonce of the signature is the identity")]
+    NonceIsIdentity,
+    #[error("The public key is the identity")]
+    InvalidPublicKey,
+    #[error("The signature is not valid for the given public key and challenge")]
+    EquationMismatch,
+    #[error("A partial signature was made against a different public nonce")]
+    MismatchedNonce,
+    #[error("At least one partial signature is required")]
+    NoPartialSignatures,
 }
 
 /// # SchnorrSignature
@@ -25,8 +44,13 @@ pub enum SchnorrSignatureError {
 /// For a concrete implementation see [RistrettoSchnorr](crate::ristretto::RistrettoSchnorr).
 ///
 /// More details on Schnorr signatures can be found at [TLU](https://tlu.tarilabs.com/cryptography/introduction-schnorr-signatures).
+///
+/// With serde, human-readable formats such as JSON represent a signature as a struct with hex-encoded `public_nonce`
+/// and `signature` fields. Binary formats use the same struct layout with each field as a byte string, e.g. 80 bytes
+/// for a Ristretto signature in `bincode`. Decoding rejects signature scalars that are not fully reduced mod the group
+/// order, so every signature has exactly one encoding.
 #[allow(non_snake_case)]
-#[derive(PartialEq, Eq, Copy, Debug, Clone, Serialize, Deserialize, Hash)]
+#[derive(PartialEq, Eq, Copy, Debug, Clone, Hash)]
 pub struct SchnorrSignature<P, K> {
     public_nonce: P,
     signature: K,
@@ -65,30 +89,92 @@ where
         Ok(Self::new(public_nonce, s))
     }
 
+    /// Sign a challenge exactly as [sign](SchnorrSignature::sign) does, but use the supplied public nonce,
+    /// `nonce_point`, instead of recalculating it from `nonce`. This saves a scalar multiplication when the caller
+    /// already holds \\( R = r.G \\), e.g. from an earlier nonce commitment round.
+    ///
+    /// `nonce_point` is trusted to match `nonce`; this is only checked in debug builds. A mismatched nonce point
+    /// produces a signature that does not verify.
+    pub fn sign_with_nonce_point(
+        secret: K,
+        nonce: K,
+        nonce_point: P,
+        challenge: &[u8],
+    ) -> Result<Self, SchnorrSignatureError>
+    where
+        K: Add<Output = K> + Mul<P, Output = P> + Mul<Output = K>,
+    {
+        debug_assert!(
+            nonce_point == P::from_secret_key(&nonce),
+            "The nonce point does not match the nonce"
+        );
+        let e = K::from_bytes(challenge).map_err(|_| SchnorrSignatureError::InvalidChallenge)?;
+        let s = e * secret + nonce;
+        Ok(Self::new(nonce_point, s))
+    }
+
     /// Returns true if this signature is valid for a public key and challenge, otherwise false. This will always return
-    /// false if `<K as ByteArray>::from_bytes(challenge)` returns an error.
+    /// false if `<K as ByteArray>::from_bytes(challenge)` returns an error. See
+    /// [try_verify_challenge](SchnorrSignature::try_verify_challenge) for the reason a signature was rejected.
+    ///
+    /// The signature scalar of a `SchnorrSignature` is always reduced, so it cannot be malleated by adding multiples of
+    /// the group order. Encodings of such scalars are rejected when a signature is deserialized.
     pub fn verify_challenge<'a>(&self, public_key: &'a P, challenge: &[u8]) -> bool
     where
         for<'b> &'b K: Mul<&'a P, Output = P>,
         for<'b> &'b P: Add<P, Output = P>,
     {
-        let e = match K::from_bytes(challenge) {
-            Ok(e) => e,
-            Err(_) => return false,
-        };
-        self.verify(public_key, &e)
+        self.try_verify_challenge(public_key, challenge).is_ok()
     }
 
-    /// Returns true if this signature is valid for a public key and challenge scalar, otherwise false.
+    /// Verify this signature for a public key and challenge, returning the reason for the failure if it is not valid.
+    /// Returns `InvalidChallenge` if `<K as ByteArray>::from_bytes(challenge)` returns an error, and otherwise behaves
+    /// as [try_verify](SchnorrSignature::try_verify).
+    pub fn try_verify_challenge<'a>(&self, public_key: &'a P, challenge: &[u8]) -> Result<(), SchnorrSignatureError>
+    where
+        for<'b> &'b K: Mul<&'a P, Output = P>,
+        for<'b> &'b P: Add<P, Output = P>,
+    {
+        let e = K::from_bytes(challenge).map_err(|_| SchnorrSignatureError::InvalidChallenge)?;
+        self.try_verify(public_key, &e)
+    }
+
+    /// Returns true if this signature is valid for a public key and challenge scalar, otherwise false. Signatures
+    /// whose public nonce is the identity, or that are checked against the identity public key, are always rejected.
     pub fn verify<'a>(&self, public_key: &'a P, challenge: &K) -> bool
     where
         for<'b> &'b K: Mul<&'a P, Output = P>,
         for<'b> &'b P: Add<P, Output = P>,
     {
+        self.try_verify(public_key, challenge).is_ok()
+    }
+
+    /// Verify this signature for a public key and challenge scalar, returning the reason for the failure if it is not
+    /// valid:
+    /// * `InvalidPublicKey` if the public key is the identity, since anyone can sign for it,
+    /// * `NonceIsIdentity` if the public nonce is the identity,
+    /// * `EquationMismatch` if \\( s.G \neq R + e.P \\).
+    pub fn try_verify<'a>(&self, public_key: &'a P, challenge: &K) -> Result<(), SchnorrSignatureError>
+    where
+        for<'b> &'b K: Mul<&'a P, Output = P>,
+        for<'b> &'b P: Add<P, Output = P>,
+    {
+        if public_key.is_identity() {
+            return Err(SchnorrSignatureError::InvalidPublicKey);
+        }
+        // A signature with an identity nonce leaks the relationship between the challenge and the secret key, and is
+        // never produced by an honest signer
+        if self.public_nonce.is_identity() {
+            return Err(SchnorrSignatureError::NonceIsIdentity);
+        }
         let lhs = self.calc_signature_verifier();
         let rhs = &self.public_nonce + challenge * public_key;
         // Implementors should make this a constant time comparison
-        lhs == rhs
+        if lhs == rhs {
+            Ok(())
+        } else {
+            Err(SchnorrSignatureError::EquationMismatch)
+        }
     }
 
     /// Returns a reference to the `s` signature component.
@@ -134,6 +220,104 @@ where
     }
 }
 
+impl<'b, P, K> Add<&'b SchnorrSignature<P, K>> for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    for<'a> &'a P: Add<&'b P, Output = P>,
+    K: SecretKey,
+    for<'a> &'a K: Add<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn add(self, rhs: &'b SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() + rhs.get_public_nonce();
+        let s = self.get_signature() + rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
+impl<P, K> Add<SchnorrSignature<P, K>> for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    for<'a, 'b> &'a P: Add<&'b P, Output = P>,
+    K: SecretKey,
+    for<'a, 'b> &'a K: Add<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn add(self, rhs: SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() + rhs.get_public_nonce();
+        let s = self.get_signature() + rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
+/// Remove a signature from an aggregate, e.g. to take a signer's partial signature out of a sum of partial
+/// signatures. Both the public nonce and the signature value are subtracted, so \\( (s_1 + s_2) - s_2 = s_1 \\).
+impl<'a, 'b, P, K> Sub<&'b SchnorrSignature<P, K>> for &'a SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    &'a P: Sub<&'b P, Output = P>,
+    K: SecretKey,
+    &'a K: Sub<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn sub(self, rhs: &'b SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() - rhs.get_public_nonce();
+        let s = self.get_signature() - rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
+impl<'a, P, K> Sub<SchnorrSignature<P, K>> for &'a SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    for<'b> &'a P: Sub<&'b P, Output = P>,
+    K: SecretKey,
+    for<'b> &'a K: Sub<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn sub(self, rhs: SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() - rhs.get_public_nonce();
+        let s = self.get_signature() - rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
+impl<'b, P, K> Sub<&'b SchnorrSignature<P, K>> for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    for<'a> &'a P: Sub<&'b P, Output = P>,
+    K: SecretKey,
+    for<'a> &'a K: Sub<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn sub(self, rhs: &'b SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() - rhs.get_public_nonce();
+        let s = self.get_signature() - rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
+impl<P, K> Sub<SchnorrSignature<P, K>> for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    for<'a, 'b> &'a P: Sub<&'b P, Output = P>,
+    K: SecretKey,
+    for<'a, 'b> &'a K: Sub<&'b K, Output = K>,
+{
+    type Output = SchnorrSignature<P, K>;
+
+    fn sub(self, rhs: SchnorrSignature<P, K>) -> SchnorrSignature<P, K> {
+        let r = self.get_public_nonce() - rhs.get_public_nonce();
+        let s = self.get_signature() - rhs.get_signature();
+        SchnorrSignature::new(r, s)
+    }
+}
+
 impl<P, K> Default for SchnorrSignature<P, K>
 where
     P: PublicKey<K = K>,
@@ -171,3 +355,301 @@ where
         Some(self.cmp(other))
     }
 }
+
+/// A borrowed byte string that serializes with `serialize_bytes`, matching the binary encoding of the key types
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// An owned byte string, deserialized from either a serde byte string or a sequence of bytes
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct ByteBufVisitor;
+
+        impl<'de> Visitor<'de> for ByteBufVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<ByteBuf, E>
+            where E: de::Error {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<ByteBuf, E>
+            where E: de::Error {
+                Ok(ByteBuf(v))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<ByteBuf, A::Error>
+            where A: SeqAccess<'de> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(64));
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(ByteBufVisitor)
+    }
+}
+
+impl<P, K> Serialize for SchnorrSignature<P, K>
+where
+    P: ByteArray,
+    K: ByteArray,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let human_readable = serializer.is_human_readable();
+        let mut state = serializer.serialize_struct("SchnorrSignature", 2)?;
+        if human_readable {
+            state.serialize_field("public_nonce", &self.public_nonce.to_hex())?;
+            state.serialize_field("signature", &self.signature.to_hex())?;
+        } else {
+            state.serialize_field("public_nonce", &Bytes(self.public_nonce.as_bytes()))?;
+            state.serialize_field("signature", &Bytes(self.signature.as_bytes()))?;
+        }
+        state.end()
+    }
+}
+
+impl<'de, P, K> Deserialize<'de> for SchnorrSignature<P, K>
+where
+    P: PublicKey<K = K>,
+    K: SecretKey,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        #[derive(Deserialize)]
+        #[serde(rename = "SchnorrSignature")]
+        struct HexSignature {
+            public_nonce: String,
+            signature: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename = "SchnorrSignature")]
+        struct BinarySignature {
+            public_nonce: ByteBuf,
+            signature: ByteBuf,
+        }
+
+        if deserializer.is_human_readable() {
+            let sig = HexSignature::deserialize(deserializer)?;
+            let public_nonce = P::from_hex(&sig.public_nonce).map_err(de::Error::custom)?;
+            let signature = canonical_scalar_from_hex(&sig.signature).map_err(de::Error::custom)?;
+            Ok(SchnorrSignature::new(public_nonce, signature))
+        } else {
+            let sig = BinarySignature::deserialize(deserializer)?;
+            let public_nonce = P::from_bytes(&sig.public_nonce.0).map_err(de::Error::custom)?;
+            let signature = canonical_scalar_from_bytes(&sig.signature.0).map_err(de::Error::custom)?;
+            Ok(SchnorrSignature::new(public_nonce, signature))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    //! A toy group is used here to show that the generic signing and verification logic does not depend on any
+    //! Ristretto specifics. Public keys are elements of the order-11 subgroup of \\( \mathbb{Z}^*_{23} \\), generated
+    //! by 2, and the group operation is multiplication mod 23. It offers no security whatsoever.
+
+    use std::{
+        convert::TryFrom,
+        ops::{Add, Mul},
+    };
+
+    use rand::{CryptoRng, Rng};
+    use serde::{Deserialize, Serialize};
+    use tari_utilities::{ByteArray, ByteArrayError};
+
+    use super::*;
+
+    const P: u8 = 23;
+    const Q: u8 = 11;
+    const G: u8 = 2;
+
+    fn mul_mod(a: u8, b: u8, m: u8) -> u8 {
+        u8::try_from(u16::from(a) * u16::from(b) % u16::from(m)).expect("the result is less than m")
+    }
+
+    fn pow_mod(base: u8, exp: u8) -> u8 {
+        (0..exp).fold(1, |acc, _| mul_mod(acc, base, P))
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    struct ToyScalar([u8; 1]);
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    struct ToyPoint([u8; 1]);
+
+    impl Default for ToyPoint {
+        fn default() -> Self {
+            ToyPoint([1])
+        }
+    }
+
+    impl ByteArray for ToyScalar {
+        fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+            match bytes {
+                [b] => Ok(ToyScalar([b % Q])),
+                _ => Err(ByteArrayError::IncorrectLength),
+            }
+        }
+
+        fn as_bytes(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl ByteArray for ToyPoint {
+        fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+            match bytes {
+                [b] if *b != 0 && *b < P && pow_mod(*b, Q) == 1 => Ok(ToyPoint([*b])),
+                [_] => Err(ByteArrayError::ConversionError("Not a subgroup element".to_string())),
+                _ => Err(ByteArrayError::IncorrectLength),
+            }
+        }
+
+        fn as_bytes(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl Add for ToyScalar {
+        type Output = ToyScalar;
+
+        fn add(self, rhs: ToyScalar) -> ToyScalar {
+            ToyScalar([(self.0[0] + rhs.0[0]) % Q])
+        }
+    }
+
+    impl Mul for ToyScalar {
+        type Output = ToyScalar;
+
+        fn mul(self, rhs: ToyScalar) -> ToyScalar {
+            ToyScalar([mul_mod(self.0[0], rhs.0[0], Q)])
+        }
+    }
+
+    impl<'a, 'b> Mul<&'b ToyPoint> for &'a ToyScalar {
+        type Output = ToyPoint;
+
+        fn mul(self, rhs: &'b ToyPoint) -> ToyPoint {
+            ToyPoint([pow_mod(rhs.0[0], self.0[0])])
+        }
+    }
+
+    impl Mul<ToyPoint> for ToyScalar {
+        type Output = ToyPoint;
+
+        fn mul(self, rhs: ToyPoint) -> ToyPoint {
+            &self * &rhs
+        }
+    }
+
+    impl<'a> Add<ToyPoint> for &'a ToyPoint {
+        type Output = ToyPoint;
+
+        fn add(self, rhs: ToyPoint) -> ToyPoint {
+            ToyPoint([mul_mod(self.0[0], rhs.0[0], P)])
+        }
+    }
+
+    impl Add for ToyPoint {
+        type Output = ToyPoint;
+
+        fn add(self, rhs: ToyPoint) -> ToyPoint {
+            &self + rhs
+        }
+    }
+
+    impl SecretKey for ToyScalar {
+        fn key_length() -> usize {
+            1
+        }
+
+        fn random<R: Rng + CryptoRng>(rng: &mut R) -> Self {
+            ToyScalar([rng.gen_range(0..Q)])
+        }
+    }
+
+    impl PublicKey for ToyPoint {
+        type K = ToyScalar;
+
+        fn from_secret_key(k: &ToyScalar) -> Self {
+            ToyPoint([pow_mod(G, k.0[0])])
+        }
+
+        fn key_length() -> usize {
+            1
+        }
+
+        fn batch_mul(scalars: &[ToyScalar], points: &[ToyPoint]) -> Self {
+            scalars
+                .iter()
+                .zip(points)
+                .fold(ToyPoint::default(), |acc, (k, p)| &acc + k * p)
+        }
+    }
+
+    type ToySchnorr = SchnorrSignature<ToyPoint, ToyScalar>;
+
+    fn scalar(k: u8) -> ToyScalar {
+        ToyScalar([k])
+    }
+
+    #[test]
+    fn generic_sign_and_verify_on_toy_group() {
+        // The group is small enough to check every key, nonce and challenge
+        for k in 1..Q {
+            let public_key = ToyPoint::from_secret_key(&scalar(k));
+            for r in 1..Q {
+                for e in 0..Q {
+                    let sig = ToySchnorr::sign(scalar(k), scalar(r), &[e]).unwrap();
+                    assert_eq!(sig.get_public_nonce(), &ToyPoint::from_secret_key(&scalar(r)));
+                    assert!(sig.verify_challenge(&public_key, &[e]));
+                    assert!(sig.verify(&public_key, &scalar(e)));
+                    let other_e = (e + 1) % Q;
+                    assert!(!sig.verify_challenge(&public_key, &[other_e]));
+                    let other_key = ToyPoint::from_secret_key(&scalar(k % (Q - 1) + 1));
+                    // A zero challenge does not involve the key at all
+                    assert_eq!(sig.verify_challenge(&other_key, &[e]), e == 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generic_checks_apply_to_toy_group() {
+        let public_key = ToyPoint::from_secret_key(&scalar(3));
+        let sig = ToySchnorr::sign(scalar(3), scalar(5), &[7]).unwrap();
+        assert_eq!(
+            sig.try_verify(&ToyPoint::default(), &scalar(7)),
+            Err(SchnorrSignatureError::InvalidPublicKey)
+        );
+        let identity_nonce = ToySchnorr::new(ToyPoint::default(), scalar(0));
+        assert_eq!(
+            identity_nonce.try_verify(&public_key, &scalar(0)),
+            Err(SchnorrSignatureError::NonceIsIdentity)
+        );
+        assert_eq!(
+            ToySchnorr::sign(scalar(3), scalar(5), &[7, 7]),
+            Err(SchnorrSignatureError::InvalidChallenge)
+        );
+        assert!(ToyPoint::from_bytes(&[5]).is_err());
+        assert!(ToyPoint::from_bytes(&[4]).is_ok());
+    }
+}
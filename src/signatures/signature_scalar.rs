@@ -0,0 +1,103 @@
+// Copyright 2022 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serde helpers for the scalar components of signatures.
+//!
+//! Secret keys are only serializable with the `serde-secrets` feature, but the scalars in a signature are public, so
+//! the signature types serialize them with these functions instead. The encoding is the same as that of a secret
+//! key: a hex string for human-readable formats and raw bytes otherwise.
+//!
+//! Decoding is strict. [ByteArray::from_bytes] reduces its input mod the group order, so without a check the
+//! encodings of _s_ and _s + l_ would decode to the same signature. Such a signature could then be re-encoded with a
+//! different hash but still verify, so non-canonical scalars are rejected instead.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use tari_utilities::{
+    hex::{self, Hex},
+    ByteArray,
+    ByteArrayError,
+};
+
+/// Decode a scalar from `bytes`, rejecting any encoding that is not fully reduced mod the group order
+pub(crate) fn canonical_scalar_from_bytes<K: ByteArray>(bytes: &[u8]) -> Result<K, ByteArrayError> {
+    let k = K::from_bytes(bytes)?;
+    if k.as_bytes() != bytes {
+        return Err(ByteArrayError::ConversionError(
+            "Non-canonical scalar encoding".to_string(),
+        ));
+    }
+    Ok(k)
+}
+
+/// Decode a scalar from a hex string, rejecting any encoding that is not fully reduced mod the group order
+pub(crate) fn canonical_scalar_from_hex<K: ByteArray>(s: &str) -> Result<K, ByteArrayError> {
+    let bytes = hex::from_hex(s).map_err(|e| ByteArrayError::ConversionError(e.to_string()))?;
+    canonical_scalar_from_bytes(&bytes)
+}
+
+pub(crate) fn serialize<K, S>(k: &K, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: ByteArray,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        k.to_hex().serialize(serializer)
+    } else {
+        serializer.serialize_bytes(k.as_bytes())
+    }
+}
+
+pub(crate) fn deserialize<'de, K, D>(deserializer: D) -> Result<K, D::Error>
+where
+    K: ByteArray,
+    D: Deserializer<'de>,
+{
+    struct ScalarVisitor<K>(PhantomData<K>);
+
+    impl<'de, K: ByteArray> Visitor<'de> for ScalarVisitor<K> {
+        type Value = K;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a signature scalar in binary format")
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<K, E>
+        where E: de::Error {
+            canonical_scalar_from_bytes(v).map_err(E::custom)
+        }
+    }
+
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        canonical_scalar_from_hex(&s).map_err(de::Error::custom)
+    } else {
+        deserializer.deserialize_bytes(ScalarVisitor(PhantomData))
+    }
+}
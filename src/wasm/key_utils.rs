@@ -383,7 +383,7 @@ pub fn check_comsig_signature(
 #[wasm_bindgen]
 pub fn secret_key_from_hex_bytes(private_key_hex: &str) -> JsValue {
     match RistrettoSecretKey::from_hex(private_key_hex) {
-        Ok(sk) => JsValue::from_serde(&sk).unwrap_or_else(|_| JsValue::from_bool(false)),
+        Ok(sk) => JsValue::from_str(&sk.to_hex()),
         Err(_) => JsValue::from_bool(false),
     }
 }
@@ -403,7 +403,7 @@ pub fn add_secret_keys(private_key_a: &str, private_key_b: &str) -> JsValue {
     };
 
     let result_key = k_a + k_b;
-    JsValue::from_serde(&result_key).unwrap()
+    JsValue::from_str(&result_key.to_hex())
 }
 
 /// A function that accepts two private keys and subtracts the second from the first. Will return false if
@@ -421,7 +421,7 @@ pub fn subtract_secret_keys(private_key_a: &str, private_key_b: &str) -> JsValue
     };
 
     let result_key = k_a - k_b;
-    JsValue::from_serde(&result_key).unwrap()
+    JsValue::from_str(&result_key.to_hex())
 }
 
 /// A function that accepts two private keys and multiplies them together and returns the result. Will return false if
@@ -439,7 +439,7 @@ pub fn multiply_secret_keys(private_key_a: &str, private_key_b: &str) -> JsValue
     };
 
     let result_key = k_a * k_b;
-    JsValue::from_serde(&result_key).unwrap()
+    JsValue::from_str(&result_key.to_hex())
 }
 
 #[cfg(test)]
@@ -899,9 +899,8 @@ mod test {
         #[wasm_bindgen_test]
         fn success_case() {
             fn it_succeeds(private_key_hex: &str, expected_sk: &RistrettoSecretKey) {
-                let sk = secret_key_from_hex_bytes(private_key_hex)
-                    .into_serde::<RistrettoSecretKey>()
-                    .unwrap();
+                let sk = secret_key_from_hex_bytes(private_key_hex).as_string().unwrap();
+                let sk = RistrettoSecretKey::from_hex(&sk).unwrap();
                 assert_eq!(sk, *expected_sk);
             }
 
@@ -918,7 +917,7 @@ mod test {
             assert!(!(subject)(k_a, k_b).as_bool().unwrap());
         }
         fn it_succeeds<F: Fn(&str, &str) -> JsValue>(subject: F, k_a: &str, k_b: &str) -> RistrettoSecretKey {
-            (subject)(k_a, k_b).into_serde::<RistrettoSecretKey>().unwrap()
+            RistrettoSecretKey::from_hex(&(subject)(k_a, k_b).as_string().unwrap()).unwrap()
         }
 
         #[wasm_bindgen_test]